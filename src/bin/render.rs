@@ -0,0 +1,383 @@
+use std::env;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use testy_rusty::camera::Projection;
+use testy_rusty::drawer::{ CameraMode, Color, ColorStyle, CullMode, DepthMode, Drawer, RenderMode, ShadingMode, Winding };
+use testy_rusty::image_export::{ save_image, ImageFormat };
+use testy_rusty::light::Light;
+use testy_rusty::math::vector4f::Vec4F;
+use testy_rusty::scene::Transform;
+use testy_rusty::texture::{ Texture, TextureSampling };
+
+const USAGE: &str = "\
+Renders an OBJ model to an image file without opening a window.
+
+USAGE:
+    render <MODEL> <OUTPUT> [OPTIONS]
+
+OUTPUT format is picked from the extension: .png, .bmp or .ppm
+
+OPTIONS:
+    --add <MODEL@X,Y,Z>   Adds another model at a world position; may be repeated
+    --size <WxH>          Resolution in pixels [default: 1920x1080]
+    --render-scale <S>    Renders at S times the resolution and upscales;
+                          between 0.125 and 1 [default: 1]
+    --pos <X,Y,Z>         Camera position [default: 0,0,0]
+    --yaw <RADIANS>       Camera yaw [default: 0]
+    --pitch <RADIANS>     Camera pitch, positive looks up [default: 0]
+    --roll <RADIANS>      Camera roll [default: 0]
+    --camera <MODE>       fly | orbit; orbit frames the whole scene from the
+                          direction given by --yaw and --pitch [default: fly]
+    --fov <DEGREES>       Vertical field of view [default: 75]
+    --projection <MODE>   perspective | orthographic [default: perspective]
+    --ortho-height <H>    World units shown top to bottom in orthographic
+                          [default: matches the perspective view]
+    --zoom <FACTOR>       Orthographic magnification [default: 1]
+    --shift <X,Y>         Off-axis lens shift in half views [default: 0,0]
+    --shading <MODE>      flat | gouraud | phong | unlit [default: flat]
+    --mode <MODE>         solid | wireframe | hidden-line | solid-edges | points
+                          [default: solid]
+    --cull <MODE>         back | front | none, for every face, overriding
+                          materials [default: back unless a material says]
+    --winding <ORDER>     ccw | cw, corner order of front faces [default: ccw]
+    --depth <MODE>        zbuffer | painter [default: zbuffer]
+    --light <R,G,B>       Color of the default directional light [default: 1,1,1]
+    --ambient <R,G,B>     Ambient light color [default: 0.1,0.1,0.1]
+    --point-light <X,Y,Z> Adds a white point light; may be repeated
+    --colors <STYLE>      continuous | retro [default: continuous]
+    --texture <IMAGE>     Texture applied to the model (.png, .bmp or .ppm)
+    --sampling <MODE>     nearest | bilinear [default: bilinear]
+    -h, --help            Print this message
+";
+
+struct Options {
+    model: String,
+    output: String,
+    extra_models: Vec<(String, Vec4F)>,
+    width: usize,
+    height: usize,
+    render_scale: f32,
+    position: Vec4F,
+    yaw: f32,
+    pitch: f32,
+    roll: f32,
+    camera: CameraMode,
+    fov: f32,
+    projection: Projection,
+    ortho_height: Option<f32>,
+    zoom: f32,
+    shift: (f32, f32),
+    shading: ShadingMode,
+    mode: RenderMode,
+    cull: Option<CullMode>,
+    winding: Winding,
+    depth: DepthMode,
+    light: Color,
+    ambient: Color,
+    point_lights: Vec<Vec4F>,
+    colors: ColorStyle,
+    texture: Option<String>,
+    sampling: TextureSampling,
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse::<T>()
+        .map_err(|_| format!("invalid value for {}: '{}'", flag, value))
+}
+
+fn parse_triple(flag: &str, value: &str) -> Result<[f32; 3], String> {
+    let parts: Vec<&str> = value.split(',').collect();
+    if parts.len() != 3 {
+        return Err(format!("invalid value for {}: '{}'", flag, value));
+    }
+
+    Ok([parse_number(flag, parts[0])?, parse_number(flag, parts[1])?, parse_number(flag, parts[2])?])
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut positional = Vec::new();
+    let mut options = Options {
+        model: String::new(),
+        output: String::new(),
+        extra_models: Vec::new(),
+        width: 1920,
+        height: 1080,
+        render_scale: 1.0,
+        position: Vec4F::default(),
+        yaw: 0.0,
+        pitch: 0.0,
+        roll: 0.0,
+        camera: CameraMode::Fly,
+        fov: 75.0,
+        projection: Projection::Perspective,
+        ortho_height: None,
+        zoom: 1.0,
+        shift: (0.0, 0.0),
+        shading: ShadingMode::Flat,
+        mode: RenderMode::Solid,
+        cull: None,
+        winding: Winding::CounterClockwise,
+        depth: DepthMode::ZBuffer,
+        light: Color::WHITE,
+        ambient: Color::gray(0.1),
+        point_lights: Vec::new(),
+        colors: ColorStyle::Continuous,
+        texture: None,
+        sampling: TextureSampling::Bilinear,
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if !arg.starts_with("--") {
+            positional.push(arg.clone());
+            continue;
+        }
+
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {}", arg))?;
+
+        match arg.as_str() {
+            "--add" => {
+                let (path, position) = value
+                    .rsplit_once('@')
+                    .ok_or_else(|| format!("invalid value for --add: '{}'", value))?;
+                let [x, y, z] = parse_triple(arg, position)?;
+                options.extra_models.push((path.to_string(), Vec4F::new(x, y, z)));
+            }
+            "--size" => {
+                let (w, h) = value
+                    .split_once('x')
+                    .ok_or_else(|| format!("invalid value for --size: '{}'", value))?;
+                options.width = parse_number("--size", w)?;
+                options.height = parse_number("--size", h)?;
+                if options.width == 0 || options.height == 0 {
+                    return Err("--size must be at least 1x1".to_string());
+                }
+            }
+            "--render-scale" => {
+                options.render_scale = parse_number(arg, value)?;
+                if !(0.125..=1.0).contains(&options.render_scale) {
+                    return Err("--render-scale must be between 0.125 and 1".to_string());
+                }
+            }
+            "--pos" => {
+                let [x, y, z] = parse_triple(arg, value)?;
+                options.position = Vec4F::new(x, y, z);
+            }
+            "--yaw" => options.yaw = parse_number(arg, value)?,
+            "--pitch" => options.pitch = parse_number(arg, value)?,
+            "--roll" => options.roll = parse_number(arg, value)?,
+            "--camera" => {
+                options.camera = match value.as_str() {
+                    "fly" => CameraMode::Fly,
+                    "orbit" => CameraMode::Orbit,
+                    _ => return Err(format!("unknown camera mode '{}'", value)),
+                }
+            }
+            "--fov" => {
+                options.fov = parse_number(arg, value)?;
+                if !(options.fov > 0.0 && options.fov < 180.0) {
+                    return Err("--fov must be between 0 and 180 degrees".to_string());
+                }
+            }
+            "--projection" => {
+                options.projection = match value.as_str() {
+                    "perspective" => Projection::Perspective,
+                    "orthographic" => Projection::Orthographic,
+                    _ => return Err(format!("unknown projection '{}'", value)),
+                }
+            }
+            "--ortho-height" => {
+                let height: f32 = parse_number(arg, value)?;
+                if height <= 0.0 {
+                    return Err("--ortho-height must be positive".to_string());
+                }
+                options.ortho_height = Some(height);
+            }
+            "--zoom" => {
+                options.zoom = parse_number(arg, value)?;
+                if options.zoom <= 0.0 {
+                    return Err("--zoom must be positive".to_string());
+                }
+            }
+            "--shift" => {
+                let (x, y) = value
+                    .split_once(',')
+                    .ok_or_else(|| format!("invalid value for --shift: '{}'", value))?;
+                options.shift = (parse_number(arg, x)?, parse_number(arg, y)?);
+            }
+            "--shading" => {
+                options.shading = match value.as_str() {
+                    "flat" => ShadingMode::Flat,
+                    "gouraud" => ShadingMode::Gouraud,
+                    "phong" => ShadingMode::Phong,
+                    "unlit" => ShadingMode::Unlit,
+                    _ => return Err(format!("unknown shading mode '{}'", value)),
+                }
+            }
+            "--mode" => {
+                options.mode = match value.as_str() {
+                    "solid" => RenderMode::Solid,
+                    "wireframe" => RenderMode::Wireframe,
+                    "hidden-line" => RenderMode::HiddenLine,
+                    "solid-edges" => RenderMode::SolidWithEdges,
+                    "points" => RenderMode::Points,
+                    _ => return Err(format!("unknown render mode '{}'", value)),
+                }
+            }
+            "--cull" => {
+                options.cull = Some(match value.as_str() {
+                    "back" => CullMode::Back,
+                    "front" => CullMode::Front,
+                    "none" => CullMode::None,
+                    _ => return Err(format!("unknown cull mode '{}'", value)),
+                })
+            }
+            "--winding" => {
+                options.winding = match value.as_str() {
+                    "ccw" => Winding::CounterClockwise,
+                    "cw" => Winding::Clockwise,
+                    _ => return Err(format!("unknown winding '{}'", value)),
+                }
+            }
+            "--depth" => {
+                options.depth = match value.as_str() {
+                    "zbuffer" => DepthMode::ZBuffer,
+                    "painter" => DepthMode::PaintersSort,
+                    _ => return Err(format!("unknown depth mode '{}'", value)),
+                }
+            }
+            "--light" => {
+                let [r, g, b] = parse_triple(arg, value)?;
+                options.light = Color::new(r, g, b);
+            }
+            "--ambient" => {
+                let [r, g, b] = parse_triple(arg, value)?;
+                options.ambient = Color::new(r, g, b);
+            }
+            "--point-light" => {
+                let [x, y, z] = parse_triple(arg, value)?;
+                options.point_lights.push(Vec4F::new(x, y, z));
+            }
+            "--colors" => {
+                options.colors = match value.as_str() {
+                    "continuous" => ColorStyle::Continuous,
+                    "retro" => ColorStyle::Retro,
+                    _ => return Err(format!("unknown color style '{}'", value)),
+                }
+            }
+            "--texture" => options.texture = Some(value.clone()),
+            "--sampling" => {
+                options.sampling = match value.as_str() {
+                    "nearest" => TextureSampling::Nearest,
+                    "bilinear" => TextureSampling::Bilinear,
+                    _ => return Err(format!("unknown sampling mode '{}'", value)),
+                }
+            }
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+
+    match positional.as_slice() {
+        [model, output] => {
+            options.model = model.clone();
+            options.output = output.clone();
+        }
+        _ => return Err("expected exactly <MODEL> and <OUTPUT>".to_string()),
+    }
+
+    if ImageFormat::from_path(options.output.as_ref()).is_none() {
+        return Err(format!("unsupported output format: {}", options.output));
+    }
+
+    Ok(options)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|a| a == "-h" || a == "--help") {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut drawer = Drawer::new(options.width, options.height);
+    drawer.set_render_scale(options.render_scale);
+    if let Err(e) = drawer.ready(&options.model) {
+        eprintln!("error: cannot load model: {}", e);
+        return ExitCode::FAILURE;
+    }
+    for (path, position) in &options.extra_models {
+        match drawer.scene.load_mesh(path) {
+            Ok(mesh) => {
+                drawer.scene.add_mesh_node(path, mesh, Transform::from_translation(position.x, position.y, position.z));
+            }
+            Err(e) => {
+                eprintln!("error: cannot load model: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    for warning in drawer.scene.warnings() {
+        eprintln!("warning: {}", warning);
+    }
+
+    drawer.camera.fov = options.fov;
+    drawer.camera.position = options.position;
+    drawer.camera.set_yaw_pitch_roll(options.yaw, options.pitch, options.roll);
+    drawer.set_camera_mode(options.camera);
+    drawer.set_projection(options.projection);
+    if let Some(height) = options.ortho_height {
+        drawer.camera.ortho_height = height;
+    }
+    drawer.camera.zoom *= options.zoom;
+    (drawer.camera.shift_x, drawer.camera.shift_y) = options.shift;
+    drawer.shading_mode = options.shading;
+    drawer.render_mode = options.mode;
+    drawer.cull_mode = options.cull;
+    for mesh in &mut drawer.scene.meshes {
+        mesh.winding = options.winding;
+    }
+    drawer.depth_mode = options.depth;
+    drawer.lights[0].color = options.light;
+    drawer.ambient = options.ambient;
+    drawer.lights.extend(options.point_lights.iter().map(|&position| Light::point(position, Color::WHITE)));
+    drawer.color_style = options.colors;
+    drawer.texture_sampling = options.sampling;
+
+    if let Some(path) = &options.texture {
+        match Texture::load(path) {
+            Ok(texture) => drawer.texture = Some(Arc::new(texture)),
+            Err(e) => {
+                eprintln!("error: cannot load texture {}: {}", path, e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    drawer.render();
+
+    let frame = if (drawer.target.width, drawer.target.height) == (options.width, options.height) {
+        drawer.target
+    } else {
+        drawer.target.scaled_to(options.width, options.height)
+    };
+
+    if let Err(e) = save_image(&frame, &options.output) {
+        eprintln!("error: cannot write {}: {}", options.output, e);
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
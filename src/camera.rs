@@ -1,12 +1,32 @@
-use crate::math::{matrix4::Mat4, vector4f::Vec4F};
+use minifb::MouseButton;
+
+use crate::input::InputState;
+use crate::math::{matrix4::Mat4, quat::Quat, vector4f::Vec4F};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    Perspective,
+    /// Parallel projection: sizes do not change with distance.
+    Orthographic,
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Camera {
     // Projection
+    pub projection: Projection,
     pub near: f32,
     pub far: f32,
     pub fov: f32, // Can't be more than 180.
     pub aspect_ratio: f32,
+    /// World units shown from the bottom to the top of an orthographic view
+    /// at zoom 1.
+    pub ortho_height: f32,
+    /// Orthographic magnification; 2 shows half as much.
+    pub zoom: f32,
+    /// Lens shift, in halves of the view's width and height towards view
+    /// space +x and +y. Anything but zero makes the projection off-axis.
+    pub shift_x: f32,
+    pub shift_y: f32,
 
     // View
     pub position: Vec4F,
@@ -14,18 +34,28 @@ pub struct Camera {
     pub target: Vec4F,
     pub up: Vec4F,
 
-    // Rotation
-    pub pitch: f32,
-    pub yaw: f32,
+    /// Rotation from the rest pose, looking along `target` with `up` up, to
+    /// where the camera looks now. Any rotation works, straight up and down
+    /// included, so camera moves can `slerp` between two of them.
+    pub orientation: Quat,
 }
 
+/// Just short of straight up or down, where yaw would stop meaning anything.
+/// `turn` and the orbit camera keep pitch within it.
+pub const MAX_PITCH: f32 = 89.0_f32 * std::f32::consts::PI / 180.0_f32;
+
 impl Camera {
     pub fn new(fov: f32, aspect_ratio: f32, near: f32, far: f32) -> Camera {
         Camera {
+            projection: Projection::Perspective,
             near,
             far,
             fov,
             aspect_ratio,
+            ortho_height: 10.0_f32,
+            zoom: 1.0_f32,
+            shift_x: 0.0_f32,
+            shift_y: 0.0_f32,
             position: Vec4F::default(),
             look_dir: Vec4F::default(),
             target: Vec4F {
@@ -40,26 +70,214 @@ impl Camera {
                 z: 0.0_f32,
                 ..Vec4F::default()
             },
-            pitch: 0.0_f32,
-            yaw: 0.0_f32,
+            orientation: Quat::IDENTITY,
         }
     }
 
     pub fn get_projection_matrix(&self) -> Mat4 {
-        Mat4::project(self.fov, self.aspect_ratio, self.near, self.far)
+        let half_height = match self.projection {
+            Projection::Perspective => self.near * (self.fov * 0.5_f32).to_radians().tan(),
+            Projection::Orthographic => self.ortho_height * 0.5_f32 / self.zoom,
+        };
+        // `aspect_ratio` is height over width.
+        let half_width = half_height / self.aspect_ratio;
+        let (x, y) = (self.shift_x * half_width, self.shift_y * half_height);
+        let (left, right, bottom, top) = (x - half_width, x + half_width, y - half_height, y + half_height);
+
+        match self.projection {
+            Projection::Perspective => Mat4::frustum(left, right, bottom, top, self.near, self.far),
+            Projection::Orthographic => Mat4::orthographic(left, right, bottom, top, self.near, self.far),
+        }
+    }
+
+    /// `ortho_height` at which an orthographic view shows things `depth`
+    /// units ahead as large as the perspective view does.
+    pub fn ortho_height_at(&self, depth: f32) -> f32 {
+        2.0_f32 * depth * (self.fov * 0.5_f32).to_radians().tan()
+    }
+
+    /// Direction of the line of sight through `point`: from the eye in
+    /// perspective, straight ahead in orthographic. Needs `look_dir`, so
+    /// call `get_view_matrix` first.
+    pub fn view_ray(&self, point: Vec4F) -> Vec4F {
+        match self.projection {
+            Projection::Perspective => point - self.position,
+            Projection::Orthographic => self.look_dir,
+        }
+    }
+
+    fn right(&self) -> Vec4F {
+        self.up.cross_product(&self.target)
+    }
+
+    /// Points the camera by angles in radians: rolled about the view axis,
+    /// pitched up, then yawed about `up`.
+    pub fn set_yaw_pitch_roll(&mut self, yaw: f32, pitch: f32, roll: f32) {
+        self.orientation = Quat::from_axis_angle(self.up, -yaw)
+            * Quat::from_axis_angle(self.right(), -pitch)
+            * Quat::from_axis_angle(self.target, roll);
+    }
+
+    /// The angles `set_yaw_pitch_roll` would take to give `orientation`.
+    /// Pitch stays within ±π/2; straight up or down, yaw is arbitrary.
+    pub fn yaw_pitch_roll(&self) -> (f32, f32, f32) {
+        let mut forward = self.orientation.rotate(self.target);
+        let forward = forward.normalize();
+        let yaw = (-forward.x).atan2(forward.z);
+        let pitch = forward.y.clamp(-1.0, 1.0).asin();
+
+        // Roll is whatever turns the unrolled up vector into the actual one.
+        let mut unrolled = *self;
+        unrolled.set_yaw_pitch_roll(yaw, pitch, 0.0);
+        let (unrolled, up) = (unrolled.orientation.rotate(self.up), self.orientation.rotate(self.up));
+        let roll = unrolled.cross_product(&up).dot_product(&forward).atan2(unrolled.dot_product(&up));
+
+        (yaw, pitch, roll)
+    }
+
+    /// Mouse-look: yaws about `up` and pitches about the camera's own right
+    /// axis. Pitch is not taken past `MAX_PITCH`, or further past it if it
+    /// already is.
+    pub fn turn(&mut self, yaw: f32, pitch: f32) {
+        let (_, current, _) = self.yaw_pitch_roll();
+        let pitch = pitch.min((MAX_PITCH - current).max(0.0)).max((-MAX_PITCH - current).min(0.0));
+
+        self.orientation = (Quat::from_axis_angle(self.up, -yaw)
+            * self.orientation
+            * Quat::from_axis_angle(self.right(), -pitch))
+        .normalize();
+    }
+
+    /// Rolls the camera about its line of sight, clockwise on screen.
+    pub fn roll(&mut self, angle: f32) {
+        self.orientation = (self.orientation * Quat::from_axis_angle(self.target, angle)).normalize();
     }
 
     pub fn get_view_matrix(&mut self) -> Mat4 {
-        let mut mat_camera_rot = Mat4::default();
-        mat_camera_rot = mat_camera_rot.rotate_y(self.yaw);
-        self.look_dir = mat_camera_rot * self.target;
+        self.look_dir = self.orientation.rotate(self.target);
         let target = self.position + self.look_dir;
 
-        let mat_camera = Mat4::point_at(self.position, target, self.up);
+        let mat_camera = Mat4::point_at(self.position, target, self.orientation.rotate(self.up));
+
+        mat_camera.quick_inverse()
+    }
+}
+
+/// Free-fly mouse-look: turns the camera by how far the mouse moved since
+/// the last frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MouseLook {
+    pub enabled: bool,
+    /// Radians per pixel of mouse movement.
+    pub sensitivity: f32,
+    /// Moving the mouse up looks down.
+    pub invert_y: bool,
+    last_position: Option<(f32, f32)>,
+}
+
+impl Default for MouseLook {
+    fn default() -> Self {
+        MouseLook { enabled: false, sensitivity: 0.005, invert_y: false, last_position: None }
+    }
+}
+
+impl MouseLook {
+    /// Yaw and pitch to add for this frame's mouse position. Nothing is
+    /// returned while disabled or while the mouse is outside the window.
+    pub fn update(&mut self, mouse_position: Option<(f32, f32)>) -> Option<(f32, f32)> {
+        let last = if self.enabled { self.last_position } else { None };
+        self.last_position = mouse_position;
+
+        let ((x, y), (last_x, last_y)) = (mouse_position?, last?);
+        // Screen y grows downwards, so moving the mouse up is negative.
+        let pitch = (last_y - y) * self.sensitivity;
+
+        Some(((x - last_x) * self.sensitivity, if self.invert_y { -pitch } else { pitch }))
+    }
+}
+
+/// Orbit camera for inspecting models: circles `target` at `distance`,
+/// looking at it. Left-drag rotates, the wheel zooms and middle-drag pans.
+/// It has no view matrix of its own; `apply` places a `Camera`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitCamera {
+    pub target: Vec4F,
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Radians per pixel of dragging.
+    pub rotate_sensitivity: f32,
+    /// Fraction of the distance covered by one wheel step.
+    pub zoom_speed: f32,
+    last_position: Option<(f32, f32)>,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        OrbitCamera {
+            target: Vec4F::new(0.0, 0.0, 0.0),
+            distance: 5.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            rotate_sensitivity: 0.01,
+            zoom_speed: 0.1,
+            last_position: None,
+        }
+    }
+}
+
+impl OrbitCamera {
+    /// Centers on the box from `min` to `max` and backs off until all of it
+    /// fits in `camera`'s field of view. Keeps the current direction.
+    pub fn frame(&mut self, min: Vec4F, max: Vec4F, camera: &Camera) {
+        self.target = (min + max) * 0.5;
+        let radius = (max - min).length() * 0.5;
+
+        // `fov` is vertical; on a window taller than wide the horizontal one
+        // is narrower and decides.
+        let mut half_fov = (camera.fov * 0.5).to_radians();
+        if camera.aspect_ratio > 1.0 {
+            half_fov = (half_fov.tan() / camera.aspect_ratio).atan();
+        }
+        self.distance = (radius / half_fov.sin()).max(radius + camera.near);
+    }
+
+    pub fn update(&mut self, input: &InputState) {
+        let last = self.last_position;
+        self.last_position = input.mouse_position;
+
+        if input.scroll != 0.0 {
+            self.distance *= (1.0 - self.zoom_speed).powf(input.scroll);
+        }
+
+        let (Some((x, y)), Some((last_x, last_y))) = (input.mouse_position, last) else { return };
+        let (dx, dy) = (x - last_x, y - last_y);
+
+        if input.is_mouse_down(MouseButton::Left) {
+            self.yaw += dx * self.rotate_sensitivity;
+            self.pitch = (self.pitch - dy * self.rotate_sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+        } else if input.is_mouse_down(MouseButton::Middle) {
+            // Roughly keeps the point under the cursor under the cursor.
+            let step = self.distance * self.rotate_sensitivity * 0.1;
+            let orientation = self.camera_orientation();
+            let right = orientation.rotate(Vec4F::new(1.0, 0.0, 0.0));
+            let up = orientation.rotate(Vec4F::new(0.0, 1.0, 0.0));
+            self.target += right * (dx * step) + up * (dy * step);
+        }
+    }
+
+    fn camera_orientation(&self) -> Quat {
+        let mut camera = Camera::default();
+        camera.set_yaw_pitch_roll(self.yaw, self.pitch, 0.0);
+        camera.orientation
+    }
 
-        let mat_view = mat_camera.quick_inverse();
+    /// Moves and turns `camera` to look at `target` from the orbit.
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.set_yaw_pitch_roll(self.yaw, self.pitch, 0.0);
 
-        mat_view
+        let forward = camera.orientation.rotate(camera.target);
+        camera.position = self.target - forward * self.distance;
     }
 }
 
@@ -1,9 +1,19 @@
-use crate::camera::Camera;
-use minifb::{ Key, Window };
+use crate::camera::{ Camera, MouseLook, OrbitCamera, Projection, MAX_PITCH };
+use crate::frame_buffer::FrameBuffer;
+use crate::input::InputState;
+use crate::light::{ Light, Surface };
+use crate::raster::{ self, Tile, TILE_ROWS };
+use crate::scene::{ MeshHandle, Scene, Transform };
+use crate::shader::{
+    FlatShader, FragmentInput, GouraudShader, PhongShader, ShaderProgram, UnlitShader, Uniforms, Varyings, VertexInput
+};
+use crate::texture::{ Texture, TextureSampling };
+use minifb::Key;
 use rusttype::{ point, Font, Scale };
-use std::{ cell::RefCell, mem::swap, ops::{Add, Div, Mul}, rc::Rc, vec };
+use rayon::prelude::*;
+use std::{ mem::swap, ops::{Add, Div, Mul}, sync::Arc, vec };
 
-pub use crate::math::{ matrix4::Mat4, mesh::Mesh, vector4f::Vec4F };
+pub use crate::math::{ color::Color, material::Material, matrix4::Mat4, mesh::{ CullMode, Mesh, MeshLoadError, Winding }, vector3f::Vec3F, vector4f::Vec4F };
 
 // Заранее хочу предупредить, что следующий код проклят всеми программистами, которые его видели, при работе с ним рекомендуется
 // 1. Позаботиться о наличии святой воды в непосредственной близости от вас
@@ -16,10 +26,15 @@ pub use crate::math::{ matrix4::Mat4, mesh::Mesh, vector4f::Vec4F };
 
 // В случае нужды рефакторинга нужно сходить и помолиться и оставить пулл реквест с предложениями или улучшениями.
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Triangle {
     pub p: [Vec4F; 3],
-    pub color: u32,
+    pub t: [Vec3F; 3],
+    /// Vertex normals, unit length in model and world space.
+    pub n: [Vec4F; 3],
+    pub color: Color,
+    /// Index into `Mesh::materials`.
+    pub material: Option<usize>,
 }
 
 impl Triangle {
@@ -28,13 +43,56 @@ impl Triangle {
     }
 }
 
+/// A triangle past the vertex shader, on its way to the rasterizer.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ShadedTriangle {
+    pub tri: Triangle,
+    /// What the vertex shader passed on for each corner.
+    pub varyings: [Varyings; 3],
+}
+
+impl ShadedTriangle {
+    fn vertex(&self, i: usize) -> Vertex {
+        let tri = &self.tri;
+        Vertex { p: tri.p[i], t: tri.t[i], n: tri.n[i], varyings: self.varyings[i] }
+    }
+
+    fn set_vertex(&mut self, i: usize, vertex: Vertex) {
+        self.tri.p[i] = vertex.p;
+        self.tri.t[i] = vertex.t;
+        self.tri.n[i] = vertex.n;
+        self.varyings[i] = vertex.varyings;
+    }
+}
+
+/// Every per-vertex attribute of a `ShadedTriangle`, so clipping can split
+/// edges without knowing what they carry.
+#[derive(Clone, Copy)]
+struct Vertex {
+    p: Vec4F,
+    t: Vec3F,
+    n: Vec4F,
+    varyings: Varyings,
+}
+
+impl Vertex {
+    fn lerp(&self, other: &Vertex, t: f32) -> Vertex {
+        Vertex {
+            p: self.p.lerp(&other.p, t),
+            t: self.t.lerp(&other.t, t),
+            n: self.n.lerp(&other.n, t),
+            varyings: self.varyings.lerp(&other.varyings, t),
+        }
+    }
+}
+
 impl Mul for Triangle {
     type Output = Triangle;
 
     fn mul(self, rhs: Self) -> Self::Output {
         Triangle {
             p: [self.p[0] * rhs.p[0], self.p[1] * rhs.p[1], self.p[2] * rhs.p[2]],
-            color: self.color,
+            ..self
         }
     }
 }
@@ -45,7 +103,7 @@ impl Mul<Mat4> for Triangle {
     fn mul(self, rhs: Mat4) -> Self::Output {
         Triangle {
             p: [rhs * self.p[0], rhs * self.p[1], rhs * self.p[2]],
-            color: self.color,
+            ..self
         }
     }
 }
@@ -56,7 +114,7 @@ impl Div<f32> for Triangle {
     fn div(self, rhs: f32) -> Self::Output {
         Triangle {
             p: [self.p[0] / rhs, self.p[1] / rhs, self.p[2] / rhs],
-            color: self.color,
+            ..self
         }
     }
 }
@@ -67,348 +125,671 @@ impl Add<Vec4F> for Triangle {
     fn add(self, rhs: Vec4F) -> Self::Output {
         Triangle {
             p: [self.p[0] + rhs, self.p[1] + rhs, self.p[2] + rhs],
-            color: self.color,
+            ..self
         }
     }
 }
 
-impl Default for Triangle {
-    fn default() -> Self {
-        Triangle {
-            p: [Vec4F::default(), Vec4F::default(), Vec4F::default()],
-            color: 0,
+/// How `Drawer::render` resolves visibility between overlapping triangles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DepthMode {
+    /// Per-pixel depth test against the depth buffer of `Drawer::target`.
+    ZBuffer,
+    /// Sort triangles back to front by `Triangle::average_z` and overdraw.
+    PaintersSort,
+}
+
+/// How triangle colors are computed before rasterization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShadingMode {
+    /// Lit once per face, at its center.
+    Flat,
+    /// Lit at the vertices from the vertex normals, colors interpolated
+    /// across the face.
+    Gouraud,
+    /// Vertex normals interpolated across the face and lit per pixel.
+    Phong,
+    /// The triangle's own color, no lighting.
+    Unlit,
+}
+
+impl ShadingMode {
+    /// The built-in shaders behind the mode.
+    pub fn program(self) -> ShaderProgram {
+        match self {
+            ShadingMode::Flat => ShaderProgram::from_shader(FlatShader),
+            ShadingMode::Gouraud => ShaderProgram::from_shader(GouraudShader),
+            ShadingMode::Phong => ShaderProgram::from_shader(PhongShader),
+            ShadingMode::Unlit => ShaderProgram::from_shader(UnlitShader),
         }
     }
 }
 
+/// How lit colors are written out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorStyle {
+    /// As computed.
+    Continuous,
+    /// Every channel snapped to 13 steps, like the original grayscale ramp.
+    Retro,
+}
+
+impl ColorStyle {
+    pub fn apply(self, color: Color) -> Color {
+        match self {
+            ColorStyle::Continuous => color,
+            ColorStyle::Retro => {
+                let snap = |c: f32| ((13.0 * c) as i32).clamp(0, 12) as f32 / 12.0;
+                Color::new(snap(color.r), snap(color.g), snap(color.b))
+            }
+        }
+    }
+}
+
+/// What `Drawer::render` draws of the triangles facing the camera.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Shaded faces.
+    Solid,
+    /// Every edge in `Drawer::line_color`, including those behind other
+    /// faces.
+    Wireframe,
+    /// Only the edges not hidden behind faces.
+    HiddenLine,
+    /// Shaded faces with their edges drawn over them.
+    SolidWithEdges,
+    /// Only the corners, nearest in front.
+    Points,
+}
+
+impl RenderMode {
+    /// Whether the faces are filled, if only into the depth buffer.
+    fn fills(self) -> bool {
+        matches!(self, RenderMode::Solid | RenderMode::HiddenLine | RenderMode::SolidWithEdges)
+    }
+}
+
+/// Which controls drive `Drawer::camera` in `handle_input`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraMode {
+    /// WASD and mouse-look.
+    Fly,
+    /// `Drawer::orbit` around the scene.
+    Orbit,
+}
+
 #[derive(Debug, Clone)]
 pub struct Drawer {
-    pub height: usize,
-    pub width: usize,
-    pub buffer: Vec<u32>,
+    pub target: FrameBuffer,
+    pub depth_mode: DepthMode,
+    pub shading_mode: ShadingMode,
+    /// Runs instead of the built-in shaders of `shading_mode` when set.
+    pub shader: Option<ShaderProgram>,
+    pub color_style: ColorStyle,
+    pub render_mode: RenderMode,
+    /// Culls every face this way when set, whatever its material or mesh
+    /// says. Otherwise `Material::cull_mode` wins over `Mesh::cull_mode`.
+    pub cull_mode: Option<CullMode>,
+    /// Color of the edges and points of the modes that draw them.
+    pub line_color: Color,
+    /// Lights of the scene, in world space. `handle_input` turns the first.
+    pub lights: Vec<Light>,
+    /// Light reaching every surface regardless of the lights.
+    pub ambient: Color,
+    /// Applied to every triangle using its `t` coordinates when set,
+    /// overriding material textures.
+    pub texture: Option<Arc<Texture>>,
+    pub texture_sampling: TextureSampling,
 
     // Я хуй знает, на сколько это мнгого, но пусть будет
-    window: Rc<RefCell<Window>>,
-    mesh: Mesh,
+    pub scene: Scene,
     project_matrix: Mat4,
-    theta: f32,
     pub camera: Camera,
+    pub mouse_look: MouseLook,
+    pub camera_mode: CameraMode,
+    pub orbit: OrbitCamera,
+    /// Number of triangles that reached the rasterizer in the last frame.
+    pub triangles_drawn: usize,
+    /// Fill screen tiles on rayon's thread pool. The frame is the same
+    /// either way.
+    pub parallel: bool,
+    /// Size of the picture `target` ends up shown at; see `resize`.
+    output_size: (usize, usize),
+    render_scale: f32,
 }
 
 impl Drawer {
-    pub fn new(width: usize, height: usize, window: Rc<RefCell<Window>>) -> Drawer {
+    pub fn new(width: usize, height: usize) -> Drawer {
         Drawer {
-            height,
-            width,
-            buffer: vec![0; width * height],
-            mesh: Mesh::default(),
+            target: FrameBuffer::new(width, height),
+            depth_mode: DepthMode::ZBuffer,
+            shading_mode: ShadingMode::Flat,
+            shader: None,
+            color_style: ColorStyle::Continuous,
+            render_mode: RenderMode::Solid,
+            cull_mode: None,
+            line_color: Color::WHITE,
+            lights: vec![Light::directional(Vec4F::new(0.0, -1.0, 1.0), Color::WHITE)],
+            ambient: Color::gray(0.1),
+            texture: None,
+            texture_sampling: TextureSampling::Bilinear,
+            scene: Scene::default(),
             project_matrix: Mat4::default(),
-            theta: 0.0_f32,
-            camera: Camera::default(),
-            window,
+            camera: Camera { aspect_ratio: height as f32 / width as f32, ..Camera::default() },
+            mouse_look: MouseLook::default(),
+            camera_mode: CameraMode::Fly,
+            orbit: OrbitCamera::default(),
+            triangles_drawn: 0,
+            parallel: true,
+            output_size: (width, height),
+            render_scale: 1.0,
         }
     }
 
-    pub fn ready(&mut self) {
-        self.mesh = self.mesh.parse_obj_file(r"src\objects\car.obj");
+    /// Adapts to a new output resolution: reallocates `target` and matches
+    /// the camera's aspect ratio, so the picture is not stretched.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.output_size = (width, height);
+        self.camera.aspect_ratio = height as f32 / width as f32;
 
-        self.project_matrix = self.camera.get_projection_matrix();
+        let scaled = |size: usize| ((size as f32 * self.render_scale).round() as usize).max(1);
+        let (width, height) = (scaled(width), scaled(height));
+        if (width, height) != (self.target.width, self.target.height) {
+            self.target.resize(width, height);
+        }
     }
 
-    pub fn update(&mut self, elapsed_time: f32) {
-        self.handle_input(elapsed_time);
+    pub fn output_size(&self) -> (usize, usize) {
+        self.output_size
+    }
 
-        let mat_rot_z: Mat4 = Mat4::default().rotate_z(self.theta);
-        let mat_rot_x: Mat4 = Mat4::default().rotate_x(self.theta * 0.5_f32);
-        let mat_trans = Mat4::default().translate(0.0_f32, 0.0_f32, 5.0_f32);
-        let mat_world: Mat4 = mat_rot_z * mat_rot_x * mat_trans;
-        let mat_view = self.camera.get_view_matrix();
+    /// Renders at `scale` times the output resolution, e.g. 0.5 for a
+    /// quarter of the pixels on slow machines. The presenter stretches the
+    /// frame back up. Clamped to `0.125..=1.0`.
+    pub fn set_render_scale(&mut self, scale: f32) {
+        self.render_scale = scale.clamp(0.125, 1.0);
+        let (width, height) = self.output_size;
+        self.resize(width, height);
+    }
 
-        let mut triangles_to_raster: Vec<Triangle> = Vec::new();
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
 
-        for tri in self.mesh.tris.clone().iter_mut() {
-            let mut tri_viewed: Triangle;
-            let mut tri_transformed: Triangle = *tri * mat_world;
+    /// Replaces the scene with the one model at `model_path`, placed five
+    /// units along +z.
+    pub fn ready(&mut self, model_path: &str) -> Result<(), MeshLoadError> {
+        let mut scene = Scene::default();
+        let mesh = scene.load_mesh(model_path)?;
+        scene.add_mesh_node(model_path, mesh, Transform::from_translation(0.0, 0.0, 5.0));
+        self.scene = scene;
 
-            let line1: Vec4F = tri_transformed.p[1] - tri_transformed.p[0];
-            let line2: Vec4F = tri_transformed.p[2] - tri_transformed.p[0];
-            let normal: Vec4F = line1.cross_product(&line2).normalize();
+        Ok(())
+    }
 
-            let camera_ray = tri_transformed.p[0] - self.camera.position;
+    pub fn update(&mut self, elapsed_time: f32, input: &InputState) {
+        self.handle_input(elapsed_time, input);
+        self.render();
+    }
 
-            if normal.dot_product(&camera_ray) < 0.0_f32 {
-                let light_direction = Vec4F::new(0.0_f32, 1.0_f32, -1.0_f32).normalize();
+    /// Renders every node of the scene from the current camera into
+    /// `target`. Does not touch input, so it can be driven without a window.
+    pub fn render(&mut self) {
+        self.project_matrix = self.camera.get_projection_matrix();
 
-                let dot_product = (0.1_f32).max(normal.dot_product(&light_direction));
+        let mat_view = self.camera.get_view_matrix();
+        let program = self.shader.clone().unwrap_or_else(|| self.shading_mode.program());
+
+        // Triangles remember their mesh so materials can be looked up.
+        let mut triangles_to_raster: Vec<(MeshHandle, ShadedTriangle)> = Vec::new();
+        // Corners of the visible triangles in clip space, before clipping,
+        // for the modes drawing edges or points.
+        let mut outlines: Vec<[Vec4F; 3]> = Vec::new();
+
+        for node_id in 0..self.scene.nodes.len() {
+            let Some(mesh) = self.scene.nodes[node_id].mesh else { continue };
+            let mat_world = self.scene.world_matrix(node_id);
+            self.transform_mesh(mesh, mat_world, mat_view, &program, &mut triangles_to_raster, &mut outlines);
+        }
 
-                let col = Self::get_color(dot_product);
-                tri_transformed.color = col;
+        if self.depth_mode == DepthMode::PaintersSort {
+            triangles_to_raster.sort_by(|(_, a), (_, b)| {
+                b.tri.average_z().partial_cmp(&a.tri.average_z()).unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
 
-                tri_viewed = tri_transformed * mat_view;
-                tri_viewed.color = tri_transformed.color;
+        self.target.clear(0);
+
+        if self.render_mode.fills() {
+            let raster_triangles: Vec<RasterTriangle> = triangles_to_raster
+                .iter()
+                .map(|&(mesh, ShadedTriangle { tri, varyings })| RasterTriangle {
+                    surface: self.surface(mesh, &tri),
+                    texture: self.texture_for(mesh, &tri),
+                    tri,
+                    varyings,
+                })
+                .collect();
+            self.fill_triangles(&raster_triangles, &program);
+        }
 
-                let clipped: Vec<Triangle> = self.clip_against_plane(
-                    &mut Vec4F::new(0.0_f32, 0.0_f32, 0.1_f32),
-                    &mut Vec4F::new(0.0_f32, 0.0_f32, 1.0_f32),
-                    &mut tri_viewed
-                );
+        match self.render_mode {
+            RenderMode::Solid => {}
+            RenderMode::Wireframe => self.draw_edges(&outlines, false),
+            RenderMode::HiddenLine | RenderMode::SolidWithEdges => self.draw_edges(&outlines, true),
+            RenderMode::Points => self.draw_points(&outlines),
+        }
 
-                self.project_triangle(&mut tri_viewed, clipped, &mut triangles_to_raster)
+        self.triangles_drawn = triangles_to_raster.len();
+    }
+
+    /// Fills screen-space triangles in order. In parallel, every tile gets
+    /// the triangles overlapping it, still in order, so the frame comes out
+    /// the same as on one thread.
+    fn fill_triangles(&mut self, triangles: &[RasterTriangle], program: &ShaderProgram) {
+        // Moved out for the fill, so the tiles can hold it while the state
+        // borrows the rest of the drawer.
+        let mut target = std::mem::replace(&mut self.target, FrameBuffer::new(0, 0));
+        let state = self.raster_state(program);
+
+        if !self.parallel {
+            let mut tile = Tile::whole(&mut target);
+            for triangle in triangles {
+                triangle.fill(&mut tile, &state);
             }
+            self.target = target;
+            return;
         }
 
-        triangles_to_raster.sort_by(|a, b| {
-            b.average_z().partial_cmp(&a.average_z()).unwrap_or(std::cmp::Ordering::Equal)
-        });
+        let mut tiles = Tile::split(&mut target, TILE_ROWS);
+        let mut bins: Vec<Vec<usize>> = vec![Vec::new(); tiles.len()];
+        for (i, triangle) in triangles.iter().enumerate() {
+            let (top, bottom) = triangle.rows();
+            let first = top.max(0) as usize / TILE_ROWS;
+            let last = (bottom.max(0) as usize / TILE_ROWS).min(bins.len() - 1);
+            for bin in bins.iter_mut().take(last + 1).skip(first) {
+                bin.push(i);
+            }
+        }
 
-        self.fill(0, 0, self.width as i32, self.height as i32, 0);
-
-        let planes = [
-            (
-                Vec4F { x: 0.0, y: 0.0, z: 0.0, ..Vec4F::default() },
-                Vec4F { x: 0.0, y: 1.0, z: 0.0, ..Vec4F::default() },
-            ),
-            (
-                Vec4F { x: 0.0, y: (self.height as f32) - 1.0, z: 0.0, ..Vec4F::default() },
-                Vec4F { x: 0.0, y: -1.0, z: 0.0, ..Vec4F::default() },
-            ),
-            (
-                Vec4F { x: 0.0, y: 0.0, z: 0.0, ..Vec4F::default() },
-                Vec4F { x: 1.0, y: 0.0, z: 0.0, ..Vec4F::default() },
-            ),
-            (
-                Vec4F { x: (self.width as f32) - 1.0, y: 0.0, z: 0.0, ..Vec4F::default() },
-                Vec4F { x: -1.0, y: 0.0, z: 0.0, ..Vec4F::default() },
-            ),
-        ];
-
-        for tri_to_raster in triangles_to_raster.iter() {
-            let mut list_triangles: Vec<Triangle> = Vec::new();
-            list_triangles.push(*tri_to_raster);
-
-            for (ref mut plane_pos, ref mut plane_normal) in planes {
-                let mut new_list_triangles = Vec::new();
-                for test in list_triangles.drain(..) {
-                    let tris_to_add = self.clip_against_plane(
-                        plane_pos,
-                        plane_normal,
-                        &mut test.clone()
-                    );
-                    new_list_triangles.extend(tris_to_add);
-                }
-                list_triangles = new_list_triangles;
+        tiles.par_iter_mut().zip(bins).for_each(|(tile, bin)| {
+            for i in bin {
+                triangles[i].fill(tile, &state);
             }
+        });
+        self.target = target;
+    }
 
-            for t in list_triangles {
-                self.fill_triangle_from(t);
-                // self.draw_triangle_from(t);
+    /// Draws the edges of triangles given by their clip-space corners in
+    /// `line_color`, each cut to the view volume. With `depth_test`, only
+    /// where they are not behind the depth buffer.
+    fn draw_edges(&mut self, outlines: &[[Vec4F; 3]], depth_test: bool) {
+        let col = self.line_color.to_u32();
+        for corners in outlines {
+            for i in 0..3 {
+                let Some((a, b)) = clip_segment(corners[i], corners[(i + 1) % 3]) else { continue };
+                let (a, b) = (self.project_point(a), self.project_point(b));
+                let target = &mut self.target;
+                raster::draw_line((a.x, a.y, a.z), (b.x, b.y, b.z), |x, y, z| {
+                    if !target.in_bounds(x, y) {
+                        return;
+                    }
+                    let index = target.index(x, y);
+                    if !depth_test || z <= target.depth[index] {
+                        target.color[index] = col;
+                    }
+                });
             }
         }
+    }
 
-        self.draw_string(
-            10,
-            100,
-            format!("TRIANGLES: {}", triangles_to_raster.len()).as_str(),
-            0xffffff
-        );
+    /// Draws the corners inside the view volume of triangles given in clip
+    /// space as single depth-tested pixels in `line_color`.
+    fn draw_points(&mut self, outlines: &[[Vec4F; 3]]) {
+        let col = self.line_color.to_u32();
+        for p in outlines.iter().flatten() {
+            if FRUSTUM_PLANES.iter().all(|plane| plane_distance(plane, p) >= 0.0) {
+                let p = self.project_point(*p);
+                let (x, y) = (p.x.floor() as i32, p.y.floor() as i32);
+                if self.target.in_bounds(x, y) {
+                    let index = self.target.index(x, y);
+                    if p.z < self.target.depth[index] {
+                        self.target.depth[index] = p.z;
+                        self.target.color[index] = col;
+                    }
+                }
+            }
+        }
     }
 
-    fn project_triangle(&self, tri: &mut Triangle, clipped: Vec<Triangle>, tris_to_raster: &mut Vec<Triangle>) {
-        for n in 0..clipped.len() {
-            *tri = clipped[n] * self.project_matrix;
-            tri.color = clipped[n].color;
+    /// Culls, runs `program`'s vertex shader on and projects one mesh placed
+    /// by `mat_world`, adding the visible triangles to `tris_to_raster` and,
+    /// unless the render mode is solid, their unclipped corners to
+    /// `outlines`.
+    fn transform_mesh(
+        &self,
+        mesh: MeshHandle,
+        mat_world: Mat4,
+        mat_view: Mat4,
+        program: &ShaderProgram,
+        tris_to_raster: &mut Vec<(MeshHandle, ShadedTriangle)>,
+        outlines: &mut Vec<[Vec4F; 3]>
+    ) {
+        let mat_normal = mat_world.normal_matrix();
+        let uniforms = self.uniforms();
 
-            tri.p[0] = tri.p[0] / tri.p[0].w;
-            tri.p[1] = tri.p[1] / tri.p[1].w;
-            tri.p[2] = tri.p[2] / tri.p[2].w;
+        for tri in &self.scene.meshes[mesh].tris {
+            let mut tri_viewed: Triangle;
+            let mut tri_transformed: Triangle = *tri * mat_world;
 
-            tri.p[0].x *= -1.0_f32;
-            tri.p[0].y *= -1.0_f32;
-            tri.p[1].x *= -1.0_f32;
-            tri.p[1].y *= -1.0_f32;
-            tri.p[2].x *= -1.0_f32;
-            tri.p[2].y *= -1.0_f32;
+            let line1: Vec4F = tri_transformed.p[1] - tri_transformed.p[0];
+            let line2: Vec4F = tri_transformed.p[2] - tri_transformed.p[0];
+            let normal: Vec4F = line1.cross_product(&line2).normalize();
 
-            let offset_view = Vec4F::new(1.0, 1.0, 0.0);
+            let camera_ray = self.camera.view_ray(tri_transformed.p[0]);
+
+            // The normal above points out of the front of counter-clockwise
+            // faces.
+            let counter_clockwise = self.scene.meshes[mesh].winding == Winding::CounterClockwise;
+            let front = (normal.dot_product(&camera_ray) < 0.0_f32) == counter_clockwise;
+            let cull_mode = self
+                .cull_mode
+                .or_else(|| self.material(mesh, &tri_transformed).and_then(|material| material.cull_mode))
+                .unwrap_or(self.scene.meshes[mesh].cull_mode);
+            let visible = match cull_mode {
+                CullMode::Back => front,
+                CullMode::Front => !front,
+                CullMode::None => true,
+            };
+
+            if visible {
+                // Faces seen from behind are lit on the side facing the
+                // camera: both normals are flipped for them. Computed vertex
+                // normals come out like the face normal above.
+                let facing = if front == counter_clockwise { 1.0_f32 } else { -1.0_f32 };
+                let side = if front { 1.0_f32 } else { -1.0_f32 };
+                let side = if self.scene.meshes[mesh].computed_normals && !counter_clockwise { -side } else { side };
+                let normal = normal * facing;
+                tri_transformed.n = tri.n.map(|n| {
+                    let mut n = mat_normal * Vec4F { w: 0.0, ..n };
+                    n.normalize() * side
+                });
+
+                let p = tri_transformed.p;
+                let face_center = (p[0] + p[1] + p[2]) / 3.0_f32;
+                let surface = self.surface(mesh, &tri_transformed);
+                let varyings = [0, 1, 2].map(|i| {
+                    let input = VertexInput {
+                        position: p[i],
+                        normal: tri_transformed.n[i],
+                        uv: tri_transformed.t[i],
+                        face_normal: normal,
+                        face_center,
+                        surface,
+                    };
+                    program.vertex.vertex(&input, &uniforms)
+                });
+
+                tri_viewed = tri_transformed * mat_view;
+                tri_viewed.color = tri_transformed.color;
 
-            *tri = *tri + offset_view;
-            tri.p[0].x *= 0.5_f32 * (self.width as f32);
-            tri.p[0].y *= 0.5_f32 * (self.height as f32);
-            tri.p[1].x *= 0.5_f32 * (self.width as f32);
-            tri.p[1].y *= 0.5_f32 * (self.height as f32);
-            tri.p[2].x *= 0.5_f32 * (self.width as f32);
-            tri.p[2].y *= 0.5_f32 * (self.height as f32);
+                let tri_clip = tri_viewed * self.project_matrix;
+                if self.render_mode != RenderMode::Solid {
+                    outlines.push(tri_clip.p);
+                }
 
-            tris_to_raster.push(*tri);
+                let shaded = ShadedTriangle { tri: tri_clip, varyings };
+                tris_to_raster.extend(clip_to_frustum(&shaded).into_iter().map(|mut shaded| {
+                    shaded.tri = self.project_triangle(shaded.tri);
+                    (mesh, shaded)
+                }));
+            }
         }
     }
 
-    fn handle_input(&mut self, elapsed_time: f32) {
-        self.window
-            .borrow()
-            .get_keys()
-            .iter()
-            .for_each(|key| {
-                match key {
-                    Key::Space => {
-                        self.camera.position.y += 8.0 * elapsed_time;
-                        if self.window.borrow().is_key_down(Key::LeftCtrl) {
-                            self.camera.position.y += 16.0 * elapsed_time;
-                        }
-                    }
-                    Key::LeftShift => {
-                        self.camera.position.y -= 8.0 * elapsed_time;
-                    }
-                    Key::W => {
-                        self.camera.position += self.camera.look_dir * (8.0 * elapsed_time);
-                        if self.window.borrow().is_key_down(Key::LeftCtrl) {
-                            self.camera.position +=
-                                self.camera.look_dir * (8.0 * elapsed_time) * 2.0;
-                        }
-                    }
-                    Key::S => {
-                        self.camera.position -= self.camera.look_dir * (8.0 * elapsed_time);
-                    }
-                    Key::A => {
-                        self.camera.yaw -= 2.0 * elapsed_time;
-                    }
-                    Key::D => {
-                        self.camera.yaw += 2.0 * elapsed_time;
-                    }
-                    _ => {}
-                }
-            });
+    fn material(&self, mesh: MeshHandle, tri: &Triangle) -> Option<&Material> {
+        tri.material.and_then(|index| self.scene.meshes[mesh].materials.get(index))
     }
 
-    fn get_color(lum: f32) -> u32 {
-        let pixel_bw = (13.0 * lum) as i32;
-
-        let color = match pixel_bw {
-            0 => 0x000000,
-            1 => 0x151515,
-            2 => 0x2a2a2a,
-            3 => 0x3f3f3f,
-            4 => 0x555555,
-            5 => 0x6a6a6a,
-            6 => 0x7f7f7f,
-            7 => 0x949494,
-            8 => 0xaaaaaa,
-            9 => 0xbfbfbf,
-            10 => 0xd4d4d4,
-            11 => 0xe9e9e9,
-            12 => 0xffffff,
-            _ => 0x000000,
-        };
+    /// Lighting inputs of a triangle. The diffuse color is the material's,
+    /// white when the material is textured, or the triangle's own color.
+    fn surface(&self, mesh: MeshHandle, tri: &Triangle) -> Surface {
+        match self.material(mesh, tri) {
+            Some(material) => Surface {
+                diffuse: if material.diffuse_map.is_some() { Color::WHITE } else { material.diffuse },
+                specular: material.specular,
+                shininess: material.shininess,
+            },
+            None => Surface { diffuse: tri.color, specular: Color::BLACK, shininess: 0.0 },
+        }
+    }
 
-        color
+    /// `Drawer::texture` overrides the texture from the triangle's material.
+    fn texture_for(&self, mesh: MeshHandle, tri: &Triangle) -> Option<Arc<Texture>> {
+        self.texture
+            .clone()
+            .or_else(|| self.material(mesh, tri).and_then(|material| material.diffuse_map.clone()))
     }
 
-    fn clip_against_plane(
-        &mut self,
-        plane_p: &mut Vec4F,
-        plane_n: &mut Vec4F,
-        in_tri: &mut Triangle
-    ) -> Vec<Triangle> {
-        *plane_n = plane_n.normalize();
+    /// Divides a clipped clip-space triangle by `w` and maps it to pixels.
+    /// `p.w` is left holding `1 / w`, which the rasterizer needs to
+    /// interpolate correctly under perspective.
+    fn project_triangle(&self, mut tri: Triangle) -> Triangle {
+        tri.p = tri.p.map(|p| self.project_point(p));
+        tri
+    }
 
-        let (mut out_tri1, mut out_tri2) = (Triangle::default(), Triangle::default());
+    /// Divides a clip-space point by `w` and maps it to pixels, leaving
+    /// `1 / w` in `w`.
+    fn project_point(&self, p: Vec4F) -> Vec4F {
+        let inv_w = 1.0_f32 / p.w;
 
-        let dist = |p: &mut Vec4F| {
-            let _n = p.normalize();
-            return plane_n.x * p.x + plane_n.y * p.y + plane_n.z * p.z - plane_n.dot_product(&plane_p);
-        };
+        let mut p = p / p.w;
+        p.x *= -1.0_f32;
+        p.y *= -1.0_f32;
 
-        let mut inside_points: [Vec4F; 3] = [Vec4F::default(); 3];
-        let mut inside_points_count: i32 = 0;
-        let mut outside_points: [Vec4F; 3] = [Vec4F::default(); 3];
-        let mut outside_points_count: i32 = 0;
+        p += Vec4F::new(1.0, 1.0, 0.0);
+        p.x *= 0.5_f32 * (self.target.width as f32);
+        p.y *= 0.5_f32 * (self.target.height as f32);
+        p.w = inv_w;
 
-        let d0: f32 = dist(&mut in_tri.p[0]);
-        let d1: f32 = dist(&mut in_tri.p[1]);
-        let d2: f32 = dist(&mut in_tri.p[2]);
+        p
+    }
 
-        //fucking fuck
+    /// Switches the camera controls. Entering `CameraMode::Orbit` frames the
+    /// whole scene from the direction the camera looks in.
+    pub fn set_camera_mode(&mut self, mode: CameraMode) {
+        if mode == CameraMode::Orbit {
+            self.frame_scene();
+        }
+        self.camera_mode = mode;
+    }
 
-        if d0 >= 0.0 {
-            inside_points[inside_points_count as usize] = in_tri.p[0];
-            inside_points_count += 1;
-        } else {
-            outside_points[outside_points_count as usize] = in_tri.p[0];
-            outside_points_count += 1;
+    /// Points the orbit camera at the scene's bounding box and moves the
+    /// camera there.
+    pub fn frame_scene(&mut self) {
+        let (yaw, pitch, _) = self.camera.yaw_pitch_roll();
+        self.orbit.yaw = yaw;
+        self.orbit.pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+        if let Some((min, max)) = self.scene.bounds() {
+            self.orbit.frame(min, max, &self.camera);
+            if self.camera.projection == Projection::Orthographic {
+                self.camera.ortho_height = self.camera.ortho_height_at(self.orbit.distance);
+                self.camera.zoom = 1.0;
+            }
         }
+        self.orbit.apply(&mut self.camera);
+    }
 
-        if d1 >= 0.0 {
-            inside_points[inside_points_count as usize] = in_tri.p[1];
-            inside_points_count += 1;
-        } else {
-            outside_points[outside_points_count as usize] = in_tri.p[1];
-            outside_points_count += 1;
+    /// Switches between perspective and orthographic. Going orthographic
+    /// keeps what the camera looks at the same size on screen: the orbit
+    /// target, or the middle of the scene when flying.
+    pub fn set_projection(&mut self, projection: Projection) {
+        if projection == Projection::Orthographic && self.camera.projection != projection {
+            let depth = match self.camera_mode {
+                CameraMode::Orbit => Some(self.orbit.distance),
+                CameraMode::Fly => self.scene.bounds().map(|(min, max)| {
+                    let forward = self.camera.orientation.rotate(self.camera.target);
+                    ((min + max) * 0.5 - self.camera.position).dot_product(&forward)
+                }),
+            };
+            if let Some(depth) = depth.filter(|&depth| depth > self.camera.near) {
+                self.camera.ortho_height = self.camera.ortho_height_at(depth);
+                self.camera.zoom = 1.0;
+            }
         }
+        self.camera.projection = projection;
+    }
 
-        if d2 >= 0.0 {
-            inside_points[inside_points_count as usize] = in_tri.p[2];
-            inside_points_count += 1;
-        } else {
-            outside_points[outside_points_count as usize] = in_tri.p[2];
-            outside_points_count += 1;
+    fn handle_input(&mut self, elapsed_time: f32, input: &InputState) {
+        if input.is_key_pressed(Key::Z) {
+            self.depth_mode = match self.depth_mode {
+                DepthMode::ZBuffer => DepthMode::PaintersSort,
+                DepthMode::PaintersSort => DepthMode::ZBuffer,
+            };
         }
 
-        if inside_points_count == 0 {
-            return vec![];
+        if input.is_key_pressed(Key::T) {
+            self.texture_sampling = match self.texture_sampling {
+                TextureSampling::Nearest => TextureSampling::Bilinear,
+                TextureSampling::Bilinear => TextureSampling::Nearest,
+            };
         }
 
-        if inside_points_count == 3 {
-            out_tri1 = *in_tri;
+        if input.is_key_pressed(Key::L) {
+            self.shading_mode = match self.shading_mode {
+                ShadingMode::Flat => ShadingMode::Gouraud,
+                ShadingMode::Gouraud => ShadingMode::Phong,
+                ShadingMode::Phong => ShadingMode::Unlit,
+                ShadingMode::Unlit => ShadingMode::Flat,
+            };
+        }
 
-            return vec![out_tri1];
+        if input.is_key_pressed(Key::V) {
+            self.render_mode = match self.render_mode {
+                RenderMode::Solid => RenderMode::Wireframe,
+                RenderMode::Wireframe => RenderMode::HiddenLine,
+                RenderMode::HiddenLine => RenderMode::SolidWithEdges,
+                RenderMode::SolidWithEdges => RenderMode::Points,
+                RenderMode::Points => RenderMode::Solid,
+            };
         }
 
-        if inside_points_count == 1 && outside_points_count == 2 {
-            out_tri1.color = in_tri.color;
+        if input.is_key_pressed(Key::C) {
+            self.cull_mode = match self.cull_mode {
+                None => Some(CullMode::None),
+                Some(CullMode::None) => Some(CullMode::Front),
+                Some(CullMode::Front) => Some(CullMode::Back),
+                Some(CullMode::Back) => None,
+            };
+        }
 
-            out_tri1.p[0] = inside_points[0];
-            out_tri1.p[1] = Vec4F::intersects_plane(
-                plane_p,
-                plane_n,
-                &inside_points[0],
-                &outside_points[0]
-            );
-            out_tri1.p[2] = Vec4F::intersects_plane(
-                plane_p,
-                plane_n,
-                &inside_points[0],
-                &outside_points[1]
-            );
+        if let Some(light) = self.lights.first_mut() {
+            let turn = 1.5 * elapsed_time;
+            for (key, rotation) in [
+                (Key::Left, Mat4::rotate_y(turn)),
+                (Key::Right, Mat4::rotate_y(-turn)),
+                (Key::Up, Mat4::rotate_x(turn)),
+                (Key::Down, Mat4::rotate_x(-turn)),
+            ] {
+                if input.is_key_down(key) {
+                    light.transform(rotation);
+                }
+            }
+        }
 
-            return vec![out_tri1];
+        if input.is_key_pressed(Key::R) {
+            self.color_style = match self.color_style {
+                ColorStyle::Continuous => ColorStyle::Retro,
+                ColorStyle::Retro => ColorStyle::Continuous,
+            };
         }
 
-        if inside_points_count == 2 && outside_points_count == 1 {
-            out_tri1.color = in_tri.color;
-            out_tri2.color = in_tri.color;
+        if input.is_key_pressed(Key::Minus) {
+            self.set_render_scale(self.render_scale * 0.5);
+        }
+        if input.is_key_pressed(Key::Equal) {
+            self.set_render_scale(self.render_scale * 2.0);
+        }
 
-            out_tri1.p[0] = inside_points[0];
-            out_tri1.p[1] = inside_points[1];
-            out_tri1.p[2] = Vec4F::intersects_plane(
-                plane_p,
-                plane_n,
-                &inside_points[0],
-                &outside_points[0]
-            );
+        if input.is_key_pressed(Key::P) {
+            self.set_projection(match self.camera.projection {
+                Projection::Perspective => Projection::Orthographic,
+                Projection::Orthographic => Projection::Perspective,
+            });
+        }
 
-            out_tri2.p[0] = inside_points[1];
-            out_tri2.p[1] = out_tri1.p[2];
-            out_tri2.p[2] = Vec4F::intersects_plane(
-                plane_p,
-                plane_n,
-                &inside_points[1],
-                &outside_points[0]
-            );
+        // Moving closer does nothing in orthographic, so the wheel zooms.
+        let ortho_zoom = self.camera.projection == Projection::Orthographic && input.scroll != 0.0;
+        if ortho_zoom {
+            self.camera.zoom /= (1.0 - self.orbit.zoom_speed).powf(input.scroll);
+        }
 
-            return vec![out_tri1, out_tri2];
+        if input.is_key_pressed(Key::O) {
+            self.set_camera_mode(match self.camera_mode {
+                CameraMode::Fly => CameraMode::Orbit,
+                CameraMode::Orbit => CameraMode::Fly,
+            });
+        }
+
+        if self.camera_mode == CameraMode::Orbit {
+            if input.is_key_pressed(Key::F) {
+                self.frame_scene();
+            }
+            let distance = self.orbit.distance;
+            self.orbit.update(input);
+            if ortho_zoom {
+                self.orbit.distance = distance;
+            }
+            self.orbit.apply(&mut self.camera);
+            return;
         }
 
-        vec![]
+        if input.is_key_pressed(Key::M) {
+            self.mouse_look.enabled = !self.mouse_look.enabled;
+        }
+        if let Some((yaw, pitch)) = self.mouse_look.update(input.mouse_position) {
+            self.camera.turn(yaw, pitch);
+        }
+
+        input.keys_down
+            .iter()
+            .for_each(|key| {
+                match key {
+                    Key::Space => {
+                        self.camera.position.y += 8.0 * elapsed_time;
+                        if input.is_key_down(Key::LeftCtrl) {
+                            self.camera.position.y += 16.0 * elapsed_time;
+                        }
+                    }
+                    Key::LeftShift => {
+                        self.camera.position.y -= 8.0 * elapsed_time;
+                    }
+                    Key::W => {
+                        self.camera.position += self.camera.look_dir * (8.0 * elapsed_time);
+                        if input.is_key_down(Key::LeftCtrl) {
+                            self.camera.position +=
+                                self.camera.look_dir * (8.0 * elapsed_time) * 2.0;
+                        }
+                    }
+                    Key::S => {
+                        self.camera.position -= self.camera.look_dir * (8.0 * elapsed_time);
+                    }
+                    Key::A => {
+                        self.camera.turn(-2.0 * elapsed_time, 0.0);
+                    }
+                    Key::D => {
+                        self.camera.turn(2.0 * elapsed_time, 0.0);
+                    }
+                    Key::Q => {
+                        self.camera.roll(-1.5 * elapsed_time);
+                    }
+                    Key::E => {
+                        self.camera.roll(1.5 * elapsed_time);
+                    }
+                    _ => {}
+                }
+            });
     }
 
     pub fn draw_square(&mut self, x1: i32, y1: i32, x2: i32, y2: i32, cal: u32) {
@@ -417,8 +798,9 @@ impl Drawer {
     }
 
     pub fn draw(&mut self, x: i32, y: i32, col: u32) {
-        if x >= 0 && x < (self.width as i32) && y >= 0 && y < (self.height as i32) {
-            self.buffer[(y * (self.width as i32) + x) as usize] = col;
+        if self.target.in_bounds(x, y) {
+            let index = self.target.index(x, y);
+            self.target.color[index] = col;
         }
     }
 
@@ -449,6 +831,7 @@ impl Drawer {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn draw_triangle(
         &mut self,
         x1: i32,
@@ -492,14 +875,14 @@ impl Drawer {
             self.draw(x, y, col);
             for _i in 0..dx1 {
                 if px < 0 {
-                    px = px + 2 * dy1;
+                    px += 2 * dy1;
                 } else {
                     if (dx < 0 && dy < 0) || (dx > 0 && dy > 0) {
                         y += 1;
                     } else {
                         y -= 1;
                     }
-                    px = px + 2 * (dy1 - dx1);
+                    px += 2 * (dy1 - dx1);
                 }
                 x += 1;
                 self.draw(x, y, col);
@@ -515,14 +898,14 @@ impl Drawer {
             self.draw(x, y, col);
             for _i in 0..dy1 {
                 if py <= 0 {
-                    py = py + 2 * dx1;
+                    py += 2 * dx1;
                 } else {
                     if (dx < 0 && dy < 0) || (dx > 0 && dy > 0) {
                         x += 1;
                     } else {
                         x -= 1;
                     }
-                    py = py + 2 * (dx1 - dy1);
+                    py += 2 * (dx1 - dy1);
                 }
                 y += 1;
                 self.draw(x, y, col);
@@ -538,70 +921,52 @@ impl Drawer {
             tri.p[1].y as i32,
             tri.p[2].x as i32,
             tri.p[2].y as i32,
-            tri.color
+            tri.color.to_u32()
         )
     }
 
     pub fn fill_triangle_from(&mut self, tri: Triangle) {
-        self.fill_triangle(
-            tri.p[0].x as i32,
-            tri.p[0].y as i32,
-            tri.p[1].x as i32,
-            tri.p[1].y as i32,
-            tri.p[2].x as i32,
-            tri.p[2].y as i32,
-            tri.color
-        );
+        let points = tri.p.map(|p| (p.x, p.y, p.z));
+        fill_flat(&mut Tile::whole(&mut self.target), self.depth_mode, points, tri.color.to_u32());
     }
 
-    pub fn fill_triangle(
-        &mut self,
-        x1: i32,
-        y1: i32,
-        x2: i32,
-        y2: i32,
-        x3: i32,
-        y3: i32,
-        col: u32
-    ) {
+    /// Fills a screen-space triangle given as `(x, y, depth)` points at pixel
+    /// corners. Depth is interpolated across the face, and each pixel is
+    /// depth tested unless `depth_mode` is `PaintersSort`.
+    pub fn fill_triangle(&mut self, points: [(i32, i32, f32); 3], col: u32) {
         // count_calls(self);
 
-        // Sort the points by y-coordinate
-        let mut points = [
-            (x1, y1),
-            (x2, y2),
-            (x3, y3),
-        ];
-        points.sort_by_key(|p| p.1);
-
-        let (x1, y1) = points[0];
-        let (x2, y2) = points[1];
-        let (x3, y3) = points[2];
-
-        // Calculate the slopes
-        let slope_a = if y2 - y1 != 0 { ((x2 - x1) as f32) / ((y2 - y1) as f32) } else { 0.0 };
-        let slope_b = if y3 - y1 != 0 { ((x3 - x1) as f32) / ((y3 - y1) as f32) } else { 0.0 };
-        let slope_c = if y3 - y2 != 0 { ((x3 - x2) as f32) / ((y3 - y2) as f32) } else { 0.0 };
+        let points = points.map(|(x, y, z)| (x as f32, y as f32, z));
+        fill_flat(&mut Tile::whole(&mut self.target), self.depth_mode, points, col);
+    }
 
-        // Draw the triangle
-        for y in y1..=y2 {
-            let xa = (x1 as f32) + slope_a * ((y - y1) as f32);
-            let xb = (x1 as f32) + slope_b * ((y - y1) as f32);
-            self.fill_line(xa.round() as i32, xb.round() as i32, y, col);
-        }
-        for y in y2..=y3 {
-            let xa = (x2 as f32) + slope_c * ((y - y2) as f32);
-            let xb = (x1 as f32) + slope_b * ((y - y1) as f32);
-            self.fill_line(xa.round() as i32, xb.round() as i32, y, col);
+    fn uniforms(&self) -> Uniforms<'_> {
+        Uniforms {
+            lights: &self.lights,
+            ambient: self.ambient,
+            eye: self.camera.position,
+            color_style: self.color_style,
         }
     }
 
-    fn fill_line(&mut self, mut sx: i32, mut ex: i32, ny: i32, col: u32) {
-        if sx > ex {
-            swap(&mut sx, &mut ex);
+    /// Settings the pixel loops need, so that tiles can be filled without
+    /// the rest of the `Drawer`.
+    fn raster_state<'a>(&'a self, program: &'a ShaderProgram) -> RasterState<'a> {
+        RasterState {
+            depth_mode: self.depth_mode,
+            texture_sampling: self.texture_sampling,
+            uniforms: self.uniforms(),
+            program,
+            depth_only: self.render_mode == RenderMode::HiddenLine,
+            offset_depth: matches!(self.render_mode, RenderMode::HiddenLine | RenderMode::SolidWithEdges),
         }
-        for x in sx..=ex {
-            self.draw(x, ny, col);
+    }
+
+    /// Writes a pixel only if it is closer than what the depth buffer holds.
+    pub fn draw_depth(&mut self, x: i32, y: i32, z: f32, col: u32) {
+        if let Some(index) = depth_index(&self.target, self.depth_mode, x, y, z) {
+            self.target.depth[index] = z;
+            self.target.color[index] = col;
         }
     }
 
@@ -614,14 +979,14 @@ impl Drawer {
         if x < 0 {
             x = 0;
         }
-        if x >= (self.width as i32) {
-            x = (self.width as i32) - 1;
+        if x >= (self.target.width as i32) {
+            x = (self.target.width as i32) - 1;
         }
         if y < 0 {
             y = 0;
         }
-        if y >= (self.height as i32) {
-            y = (self.height as i32) - 1;
+        if y >= (self.target.height as i32) {
+            y = (self.target.height as i32) - 1;
         }
     }
 
@@ -637,7 +1002,7 @@ impl Drawer {
 
     pub fn draw_string(&mut self, x: i32, y: i32, string: &str, col: u32) {
         let font: Font<'static> = Font::try_from_bytes(
-            include_bytes!(r"assets\pixelfont.ttf") as &[u8]
+            include_bytes!("assets/pixelfont.ttf") as &[u8]
         ).unwrap();
         let height: f32 = 20f32; // adjust as needed
         let scale = Scale {
@@ -663,6 +1028,226 @@ impl Drawer {
     }
 
     fn get(&self, x: i32, y: i32) -> u32 {
-        self.buffer[(y as usize) * self.width + (x as usize)]
+        self.target.get(x, y)
+    }
+}
+
+/// Index of the pixel if it is on screen and passes the depth test.
+fn depth_index(target: &FrameBuffer, depth_mode: DepthMode, x: i32, y: i32, z: f32) -> Option<usize> {
+    if !target.in_bounds(x, y) {
+        return None;
+    }
+    let index = target.index(x, y);
+    if depth_mode == DepthMode::PaintersSort || z < target.depth[index] {
+        Some(index)
+    } else {
+        None
+    }
+}
+
+/// Per-frame settings shared by every pixel of every tile.
+#[derive(Clone, Copy, Debug)]
+struct RasterState<'a> {
+    depth_mode: DepthMode,
+    texture_sampling: TextureSampling,
+    uniforms: Uniforms<'a>,
+    program: &'a ShaderProgram,
+    /// Skip the fragment shader and write only depth, for hidden-line.
+    depth_only: bool,
+    /// Push faces back by `depth_offset` so edges drawn over them win.
+    offset_depth: bool,
+}
+
+/// A screen-space triangle ready to be filled, with what it needs from
+/// its mesh looked up.
+#[derive(Clone, Debug)]
+struct RasterTriangle {
+    tri: Triangle,
+    varyings: [Varyings; 3],
+    surface: Surface,
+    texture: Option<Arc<Texture>>,
+}
+
+impl RasterTriangle {
+    /// Pixel rows the triangle may cover.
+    fn rows(&self) -> (i32, i32) {
+        let ys = self.tri.p.map(|p| p.y);
+        (ys[0].min(ys[1]).min(ys[2]).floor() as i32, ys[0].max(ys[1]).max(ys[2]).ceil() as i32)
+    }
+
+    /// Runs the fragment shader on every covered pixel that passes the
+    /// depth test. Expects the `1 / w` that `project_triangle` leaves in
+    /// `p.w`.
+    fn fill(&self, tile: &mut Tile, state: &RasterState) {
+        let tri = &self.tri;
+        let texture = self.texture.as_deref();
+
+        // Interpolating `1 / w` once per corner gives the corners' weights
+        // with perspective correction; the varyings are blended from those.
+        let points = [0, 1, 2].map(|i| {
+            let p = tri.p[i];
+            let mut attributes = [p.z, 0.0, 0.0, 0.0];
+            attributes[i + 1] = p.w;
+            (p.x, p.y, attributes)
+        });
+
+        let offset = if state.offset_depth { depth_offset(&tri.p) } else { 0.0 };
+
+        raster::fill_triangle(points, tile.rows(), tile.columns(), |x, y, &[z, a, b, c]| {
+            let Some(index) = tile.depth_index(state.depth_mode, x, y, z + offset) else { return };
+            if state.depth_only {
+                tile.depth[index] = z + offset;
+                return;
+            }
+
+            let sum = a + b + c;
+            let weights = [a / sum, b / sum, c / sum];
+            let t = tri.t;
+            let uv = Vec3F {
+                u: t[0].u * weights[0] + t[1].u * weights[1] + t[2].u * weights[2],
+                v: t[0].v * weights[0] + t[1].v * weights[1] + t[2].v * weights[2],
+                w: t[0].w * weights[0] + t[1].w * weights[1] + t[2].w * weights[2],
+            };
+            let input = FragmentInput {
+                x,
+                y,
+                depth: z,
+                uv,
+                texel: texture.map_or(Color::WHITE, |texture| texture.sample(uv.u, uv.v, state.texture_sampling)),
+                surface: self.surface,
+            };
+
+            let varyings = Varyings::blend(&self.varyings, weights);
+            if let Some(color) = state.program.fragment.fragment(&varyings, &input, &state.uniforms) {
+                tile.depth[index] = z + offset;
+                tile.color[index] = color.to_u32();
+            }
+        });
+    }
+}
+
+/// Smallest depth offset that keeps a face behind lines along its edges.
+const EDGE_DEPTH_BIAS: f32 = 1e-6;
+
+/// How far to push a screen-space face back so that its edges, drawn as
+/// lines, pass the depth test against it: lines are sampled up to a pixel
+/// away from the centers the face is filled at, so this is the most its
+/// depth changes over a pixel, like `glPolygonOffset(1, 1)`.
+fn depth_offset(p: &[Vec4F; 3]) -> f32 {
+    let (e1, e2) = (p[1] - p[0], p[2] - p[0]);
+    let area = e1.x * e2.y - e2.x * e1.y;
+    if area == 0.0 {
+        return EDGE_DEPTH_BIAS;
+    }
+    let dz_dx = (e1.z * e2.y - e2.z * e1.y) / area;
+    let dz_dy = (e2.z * e1.x - e1.z * e2.x) / area;
+
+    dz_dx.abs().max(dz_dy.abs()) + EDGE_DEPTH_BIAS
+}
+
+fn fill_flat(tile: &mut Tile, depth_mode: DepthMode, points: [(f32, f32, f32); 3], col: u32) {
+    raster::fill_triangle(points.map(|(x, y, z)| (x, y, [z])), tile.rows(), tile.columns(), |x, y, &[z]| {
+        if let Some(index) = tile.depth_index(depth_mode, x, y, z) {
+            tile.depth[index] = z;
+            tile.color[index] = col;
+        }
+    });
+}
+
+/// Planes bounding the view volume in clip space, before the divide by `w`.
+/// A point is inside all of them when `-w <= x <= w`, `-w <= y <= w` and
+/// `0 <= z <= w`, the last two being the near and far planes.
+const FRUSTUM_PLANES: [Vec4F; 6] = [
+    Vec4F { x: 1.0, y: 0.0, z: 0.0, w: 1.0 },
+    Vec4F { x: -1.0, y: 0.0, z: 0.0, w: 1.0 },
+    Vec4F { x: 0.0, y: 1.0, z: 0.0, w: 1.0 },
+    Vec4F { x: 0.0, y: -1.0, z: 0.0, w: 1.0 },
+    Vec4F { x: 0.0, y: 0.0, z: 1.0, w: 0.0 },
+    Vec4F { x: 0.0, y: 0.0, z: -1.0, w: 1.0 },
+];
+
+/// Signed distance of a clip-space point from a plane, scaled by `w`;
+/// positive inside.
+fn plane_distance(plane: &Vec4F, p: &Vec4F) -> f32 {
+    plane.x * p.x + plane.y * p.y + plane.z * p.z + plane.w * p.w
+}
+
+/// Cuts a clip-space segment down to the part inside every plane of
+/// `FRUSTUM_PLANES`, if any.
+fn clip_segment(mut a: Vec4F, mut b: Vec4F) -> Option<(Vec4F, Vec4F)> {
+    for plane in &FRUSTUM_PLANES {
+        let (da, db) = (plane_distance(plane, &a), plane_distance(plane, &b));
+        if da < 0.0 && db < 0.0 {
+            return None;
+        }
+        if da < 0.0 {
+            a = a.lerp(&b, da / (da - db));
+        } else if db < 0.0 {
+            b = b.lerp(&a, db / (db - da));
+        }
+    }
+
+    Some((a, b))
+}
+
+/// Cuts a clip-space triangle down to the view volume, as triangles with
+/// the original winding.
+pub fn clip_to_frustum(tri: &ShadedTriangle) -> Vec<ShadedTriangle> {
+    let mut clipped = vec![*tri];
+    for plane in FRUSTUM_PLANES {
+        clipped = clipped.iter().flat_map(|tri| clip_against_plane(plane, tri)).collect();
+    }
+
+    clipped
+}
+
+/// Cuts a clip-space triangle down to the part where `plane · p >= 0`, as
+/// zero, one or two triangles with the original winding. The new corners
+/// get every vertex attribute interpolated to where the edges cross.
+fn clip_against_plane(plane: Vec4F, in_tri: &ShadedTriangle) -> Vec<ShadedTriangle> {
+    let dist = |p: &Vec4F| plane_distance(&plane, p);
+
+    // Clipped triangles keep the color and material of the original.
+    let (mut out_tri1, mut out_tri2) = (*in_tri, *in_tri);
+
+    let inside = [0, 1, 2].map(|i| dist(&in_tri.tri.p[i]) >= 0.0);
+    let inside_points_count = inside.iter().filter(|&&inside| inside).count();
+
+    // Where the edge from `inside` to `outside` crosses the plane. Distances
+    // are linear in clip space, so so is the crossing.
+    let intersect = |inside: &Vertex, outside: &Vertex| {
+        let (a, b) = (dist(&inside.p), dist(&outside.p));
+        inside.lerp(outside, a / (a - b))
+    };
+
+    // The corners starting from the one on its own side of the plane, still
+    // in their original order around the triangle.
+    let lone = (0..3).find(|&i| inside[i] == (inside_points_count == 1)).unwrap_or(0);
+    let [a, b, c] = [0, 1, 2].map(|k| in_tri.vertex((lone + k) % 3));
+
+    match inside_points_count {
+        3 => vec![*in_tri],
+        1 => {
+            out_tri1.set_vertex(0, a);
+            out_tri1.set_vertex(1, intersect(&a, &b));
+            out_tri1.set_vertex(2, intersect(&a, &c));
+
+            vec![out_tri1]
+        }
+        2 => {
+            // `a` is outside; the rest is the quad `b`, `c`, `ca`, `ab`.
+            let (ab, ca) = (intersect(&b, &a), intersect(&c, &a));
+
+            out_tri1.set_vertex(0, b);
+            out_tri1.set_vertex(1, c);
+            out_tri1.set_vertex(2, ca);
+
+            out_tri2.set_vertex(0, b);
+            out_tri2.set_vertex(1, ca);
+            out_tri2.set_vertex(2, ab);
+
+            vec![out_tri1, out_tri2]
+        }
+        _ => vec![],
     }
 }
@@ -0,0 +1,59 @@
+/// Plain offscreen render target: a 0x00RRGGBB color buffer plus the matching
+/// depth buffer. `Drawer` renders into one of these and knows nothing about
+/// where the pixels end up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameBuffer {
+    pub width: usize,
+    pub height: usize,
+    pub color: Vec<u32>,
+    pub depth: Vec<f32>,
+}
+
+impl FrameBuffer {
+    pub fn new(width: usize, height: usize) -> FrameBuffer {
+        FrameBuffer {
+            width,
+            height,
+            color: vec![0; width * height],
+            depth: vec![f32::INFINITY; width * height],
+        }
+    }
+
+    /// Reallocates for a new resolution. The contents are cleared.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        *self = FrameBuffer::new(width, height);
+    }
+
+    /// Nearest-neighbour copy at another resolution, e.g. to upscale a frame
+    /// rendered at a lower resolution.
+    pub fn scaled_to(&self, width: usize, height: usize) -> FrameBuffer {
+        let mut scaled = FrameBuffer::new(width, height);
+        for y in 0..height {
+            let src_y = y * self.height / height;
+            for x in 0..width {
+                let src = src_y * self.width + x * self.width / width;
+                scaled.color[y * width + x] = self.color[src];
+                scaled.depth[y * width + x] = self.depth[src];
+            }
+        }
+
+        scaled
+    }
+
+    pub fn clear(&mut self, col: u32) {
+        self.color.fill(col);
+        self.depth.fill(f32::INFINITY);
+    }
+
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && x < (self.width as i32) && y >= 0 && y < (self.height as i32)
+    }
+
+    pub fn index(&self, x: i32, y: i32) -> usize {
+        (y as usize) * self.width + (x as usize)
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> u32 {
+        self.color[self.index(x, y)]
+    }
+}
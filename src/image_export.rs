@@ -0,0 +1,193 @@
+use std::fs::File;
+use std::io::{ self, BufWriter, Write };
+use std::path::Path;
+
+use crate::frame_buffer::FrameBuffer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    Bmp,
+    Png,
+}
+
+impl ImageFormat {
+    /// Picks the format from the file extension, case-insensitively.
+    pub fn from_path(path: &Path) -> Option<ImageFormat> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "ppm" => Some(ImageFormat::Ppm),
+            "bmp" => Some(ImageFormat::Bmp),
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+/// Writes `frame` to `path`, choosing the encoder from the extension.
+pub fn save_image<P: AsRef<Path>>(frame: &FrameBuffer, path: P) -> io::Result<()> {
+    let path = path.as_ref();
+    let format = ImageFormat::from_path(path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unsupported image extension: {}", path.display()),
+        )
+    })?;
+
+    let bytes = encode(frame, format);
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(&bytes)?;
+    writer.flush()
+}
+
+pub fn encode(frame: &FrameBuffer, format: ImageFormat) -> Vec<u8> {
+    match format {
+        ImageFormat::Ppm => encode_ppm(frame),
+        ImageFormat::Bmp => encode_bmp(frame),
+        ImageFormat::Png => encode_png(frame),
+    }
+}
+
+fn rgb(col: u32) -> [u8; 3] {
+    [(col >> 16) as u8, (col >> 8) as u8, col as u8]
+}
+
+/// Binary (P6) PPM.
+pub fn encode_ppm(frame: &FrameBuffer) -> Vec<u8> {
+    let mut out = format!("P6\n{} {}\n255\n", frame.width, frame.height).into_bytes();
+    out.reserve(frame.color.len() * 3);
+    for &col in frame.color.iter() {
+        out.extend_from_slice(&rgb(col));
+    }
+
+    out
+}
+
+/// Uncompressed 24-bit BMP with bottom-up rows.
+pub fn encode_bmp(frame: &FrameBuffer) -> Vec<u8> {
+    let row_size = (frame.width * 3 + 3) & !3;
+    let pixel_data_size = row_size * frame.height;
+    let file_size = 14 + 40 + pixel_data_size;
+
+    let mut out = Vec::with_capacity(file_size);
+
+    // BITMAPFILEHEADER
+    out.extend_from_slice(b"BM");
+    out.extend_from_slice(&(file_size as u32).to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.extend_from_slice(&(14u32 + 40).to_le_bytes());
+
+    // BITMAPINFOHEADER
+    out.extend_from_slice(&40u32.to_le_bytes());
+    out.extend_from_slice(&(frame.width as i32).to_le_bytes());
+    out.extend_from_slice(&(frame.height as i32).to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&24u16.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.extend_from_slice(&(pixel_data_size as u32).to_le_bytes());
+    out.extend_from_slice(&2835i32.to_le_bytes());
+    out.extend_from_slice(&2835i32.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+
+    for y in (0..frame.height).rev() {
+        let row = &frame.color[y * frame.width..(y + 1) * frame.width];
+        for &col in row {
+            let [r, g, b] = rgb(col);
+            out.extend_from_slice(&[b, g, r]);
+        }
+        out.resize(out.len() + row_size - frame.width * 3, 0);
+    }
+
+    out
+}
+
+/// 8-bit RGB PNG. The zlib stream only uses stored (uncompressed) deflate
+/// blocks, which every decoder has to accept.
+pub fn encode_png(frame: &FrameBuffer) -> Vec<u8> {
+    let mut raw = Vec::with_capacity((frame.width * 3 + 1) * frame.height);
+    for y in 0..frame.height {
+        // Filter type 0 (None) for every scanline.
+        raw.push(0);
+        for &col in &frame.color[y * frame.width..(y + 1) * frame.width] {
+            raw.extend_from_slice(&rgb(col));
+        }
+    }
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(frame.width as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(frame.height as u32).to_be_bytes());
+    // Bit depth 8, color type 2 (RGB), deflate, adaptive filtering, no interlace.
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']);
+    write_png_chunk(&mut out, b"IHDR", &ihdr);
+    write_png_chunk(&mut out, b"IDAT", &zlib_store(&raw));
+    write_png_chunk(&mut out, b"IEND", &[]);
+
+    out
+}
+
+fn write_png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn zlib_store(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xFFFF;
+
+    let blocks = data.len().div_ceil(MAX_BLOCK).max(1);
+    let mut out = Vec::with_capacity(data.len() + blocks * 5 + 6);
+    // CMF/FLG: deflate with a 32K window, no preset dictionary, fastest level.
+    out.extend_from_slice(&[0x78, 0x01]);
+
+    let mut chunks = data.chunks(MAX_BLOCK).peekable();
+    if chunks.peek().is_none() {
+        out.extend_from_slice(&[1, 0x00, 0x00, 0xFF, 0xFF]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let is_final = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+        out.push(is_final as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 is the largest run that cannot overflow `b` before the modulo.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+
+    (b << 16) | a
+}
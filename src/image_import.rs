@@ -0,0 +1,438 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::image_export::ImageFormat;
+
+/// Decoded image as 0x00RRGGBB pixels, row by row from the top.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn pack(r: u8, g: u8, b: u8) -> u32 {
+    ((r as u32) << 16) | ((g as u32) << 8) | b as u32
+}
+
+/// Reads an image, picking the decoder from the file extension.
+pub fn load_image<P: AsRef<Path>>(path: P) -> io::Result<Image> {
+    let path = path.as_ref();
+    let format = ImageFormat::from_path(path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unsupported image extension: {}", path.display()),
+        )
+    })?;
+
+    decode(&fs::read(path)?, format)
+}
+
+pub fn decode(bytes: &[u8], format: ImageFormat) -> io::Result<Image> {
+    match format {
+        ImageFormat::Ppm => decode_ppm(bytes),
+        ImageFormat::Bmp => decode_bmp(bytes),
+        ImageFormat::Png => decode_png(bytes),
+    }
+}
+
+/// Binary (P6) PPM with a max value of 255. Header comments are allowed.
+pub fn decode_ppm(bytes: &[u8]) -> io::Result<Image> {
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    while fields.len() < 4 {
+        match bytes.get(pos) {
+            Some(b'#') => {
+                while bytes.get(pos).is_some_and(|&b| b != b'\n') {
+                    pos += 1;
+                }
+            }
+            Some(b) if b.is_ascii_whitespace() => pos += 1,
+            Some(_) => {
+                let start = pos;
+                while bytes.get(pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+                    pos += 1;
+                }
+                fields.push(std::str::from_utf8(&bytes[start..pos]).map_err(|_| invalid("bad PPM header"))?);
+            }
+            None => return Err(invalid("truncated PPM header")),
+        }
+    }
+    // Exactly one whitespace byte separates the header from the pixels.
+    pos += 1;
+
+    if fields[0] != "P6" || fields[3] != "255" {
+        return Err(invalid("only 8-bit binary (P6) PPM is supported"));
+    }
+    let width: usize = fields[1].parse().map_err(|_| invalid("bad PPM width"))?;
+    let height: usize = fields[2].parse().map_err(|_| invalid("bad PPM height"))?;
+
+    let end = width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(3))
+        .and_then(|n| n.checked_add(pos))
+        .ok_or_else(|| invalid("bad PPM size"))?;
+    let data = bytes.get(pos..end).ok_or_else(|| invalid("truncated PPM data"))?;
+    let pixels = data.chunks(3).map(|c| pack(c[0], c[1], c[2])).collect();
+
+    Ok(Image { width, height, pixels })
+}
+
+fn le_u16(bytes: &[u8], at: usize) -> io::Result<u16> {
+    let b = bytes.get(at..at + 2).ok_or_else(|| invalid("truncated BMP header"))?;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn le_u32(bytes: &[u8], at: usize) -> io::Result<u32> {
+    let b = bytes.get(at..at + 4).ok_or_else(|| invalid("truncated BMP header"))?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Uncompressed 24 or 32-bit BMP, bottom-up or top-down.
+pub fn decode_bmp(bytes: &[u8]) -> io::Result<Image> {
+    if !bytes.starts_with(b"BM") {
+        return Err(invalid("not a BMP file"));
+    }
+    let data_offset = le_u32(bytes, 10)? as usize;
+    let width = le_u32(bytes, 18)? as i32;
+    let height = le_u32(bytes, 22)? as i32;
+    let bits = le_u16(bytes, 28)?;
+    let compression = le_u32(bytes, 30)?;
+
+    // BI_BITFIELDS (3) with 32 bits is how most tools write plain BGRA.
+    if !(bits == 24 && compression == 0 || bits == 32 && (compression == 0 || compression == 3)) {
+        return Err(invalid("only uncompressed 24 and 32-bit BMP is supported"));
+    }
+    if width <= 0 || height == 0 {
+        return Err(invalid("bad BMP size"));
+    }
+
+    let (width, top_down) = (width as usize, height < 0);
+    let height = height.unsigned_abs() as usize;
+    let bytes_per_pixel = bits as usize / 8;
+    let (row_size, size) = width
+        .checked_mul(bytes_per_pixel)
+        .map(|n| (n + 3) & !3)
+        .zip(width.checked_mul(height))
+        .ok_or_else(|| invalid("bad BMP size"))?;
+    let end = row_size
+        .checked_mul(height)
+        .and_then(|n| n.checked_add(data_offset))
+        .ok_or_else(|| invalid("bad BMP size"))?;
+    if end > bytes.len() {
+        return Err(invalid("truncated BMP data"));
+    }
+
+    let mut pixels = vec![0; size];
+    for y in 0..height {
+        let src_row = if top_down { y } else { height - 1 - y };
+        let start = data_offset + src_row * row_size;
+        let row = &bytes[start..start + width * bytes_per_pixel];
+        for (x, px) in row.chunks(bytes_per_pixel).enumerate() {
+            pixels[y * width + x] = pack(px[2], px[1], px[0]);
+        }
+    }
+
+    Ok(Image { width, height, pixels })
+}
+
+/// Non-interlaced 8-bit PNG in any color type. Alpha is dropped.
+pub fn decode_png(bytes: &[u8]) -> io::Result<Image> {
+    if !bytes.starts_with(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']) {
+        return Err(invalid("not a PNG file"));
+    }
+
+    let mut pos = 8;
+    let (mut width, mut height, mut color_type) = (0, 0, 0);
+    let mut palette: Vec<u32> = Vec::new();
+    let mut idat = Vec::new();
+    while pos + 8 <= bytes.len() {
+        let len = u32::from_be_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]]) as usize;
+        let kind = &bytes[pos + 4..pos + 8];
+        let data = bytes
+            .get(pos + 8..pos + 8 + len)
+            .ok_or_else(|| invalid("truncated PNG chunk"))?;
+        match kind {
+            b"IHDR" => {
+                if data.len() < 13 {
+                    return Err(invalid("bad PNG header"));
+                }
+                width = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+                height = u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as usize;
+                color_type = data[9];
+                if data[8] != 8 || data[12] != 0 {
+                    return Err(invalid("only 8-bit non-interlaced PNG is supported"));
+                }
+            }
+            b"PLTE" => {
+                if data.len() % 3 != 0 {
+                    return Err(invalid("bad PNG palette"));
+                }
+                palette = data.chunks(3).map(|c| pack(c[0], c[1], c[2])).collect();
+            }
+            b"IDAT" => idat.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+        pos += 12 + len;
+    }
+
+    let channels = match color_type {
+        0 => 1,
+        2 => 3,
+        3 => 1,
+        4 => 2,
+        6 => 4,
+        _ => return Err(invalid("unknown PNG color type")),
+    };
+    if width == 0 || height == 0 {
+        return Err(invalid("bad PNG size"));
+    }
+
+    // Skip the two byte zlib header; the Adler-32 trailer is not checked.
+    let raw = inflate(idat.get(2..).ok_or_else(|| invalid("truncated PNG data"))?)?;
+    let stride = width.checked_mul(channels).ok_or_else(|| invalid("bad PNG size"))?;
+    let size = stride.checked_add(1).and_then(|n| n.checked_mul(height)).ok_or_else(|| invalid("bad PNG size"))?;
+    if raw.len() < size {
+        return Err(invalid("truncated PNG data"));
+    }
+
+    let mut prev = vec![0u8; stride];
+    let mut row = vec![0u8; stride];
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let line = &raw[y * (stride + 1)..(y + 1) * (stride + 1)];
+        unfilter(line[0], &line[1..], &prev, &mut row, channels)?;
+
+        for px in row.chunks(channels) {
+            pixels.push(match color_type {
+                0 | 4 => pack(px[0], px[0], px[0]),
+                3 => *palette.get(px[0] as usize).ok_or_else(|| invalid("bad PNG palette index"))?,
+                _ => pack(px[0], px[1], px[2]),
+            });
+        }
+        std::mem::swap(&mut prev, &mut row);
+    }
+
+    Ok(Image { width, height, pixels })
+}
+
+fn unfilter(filter: u8, line: &[u8], prev: &[u8], out: &mut [u8], bpp: usize) -> io::Result<()> {
+    for i in 0..line.len() {
+        let a = if i >= bpp { out[i - bpp] as i16 } else { 0 };
+        let b = prev[i] as i16;
+        let c = if i >= bpp { prev[i - bpp] as i16 } else { 0 };
+        let predictor = match filter {
+            0 => 0,
+            1 => a,
+            2 => b,
+            3 => (a + b) / 2,
+            4 => {
+                let p = a + b - c;
+                let (pa, pb, pc) = ((p - a).abs(), (p - b).abs(), (p - c).abs());
+                if pa <= pb && pa <= pc {
+                    a
+                } else if pb <= pc {
+                    b
+                } else {
+                    c
+                }
+            }
+            _ => return Err(invalid("unknown PNG filter")),
+        };
+        out[i] = line[i].wrapping_add(predictor as u8);
+    }
+
+    Ok(())
+}
+
+/// LSB-first bit reader over a deflate stream.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, count: u32) -> io::Result<u32> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = *self.data.get(self.pos).ok_or_else(|| invalid("truncated deflate stream"))?;
+            value |= (((byte >> self.bit) & 1) as u32) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+/// Canonical Huffman code as per-length symbol counts plus the symbols
+/// sorted by code.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0u16; 16];
+        for len in 1..16 {
+            offsets[len] = offsets[len - 1] + counts[len - 1];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> io::Result<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(invalid("bad Huffman code"))
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DIST_BASE: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DIST_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+/// Raw deflate (RFC 1951) decoder.
+fn inflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut reader = BitReader { data, pos: 0, bit: 0 };
+    let mut out = Vec::new();
+
+    loop {
+        let is_final = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let at = reader.pos;
+                let header = data.get(at..at + 4).ok_or_else(|| invalid("truncated stored block"))?;
+                let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+                let block = data.get(at + 4..at + 4 + len).ok_or_else(|| invalid("truncated stored block"))?;
+                out.extend_from_slice(block);
+                reader.pos = at + 4 + len;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let lit = Huffman::new(&lengths);
+                let dist = Huffman::new(&[5; 30]);
+                inflate_block(&mut reader, &mut out, &lit, &dist)?;
+            }
+            2 => {
+                let hlit = reader.bits(5)? as usize + 257;
+                let hdist = reader.bits(5)? as usize + 1;
+                let hclen = reader.bits(4)? as usize + 4;
+
+                const ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+                let mut code_lengths = [0u8; 19];
+                for &i in ORDER.iter().take(hclen) {
+                    code_lengths[i] = reader.bits(3)? as u8;
+                }
+                let code = Huffman::new(&code_lengths);
+
+                let mut lengths = vec![0u8; hlit + hdist];
+                let mut i = 0;
+                while i < lengths.len() {
+                    let symbol = code.decode(&mut reader)?;
+                    let (value, repeat) = match symbol {
+                        0..=15 => (symbol as u8, 1),
+                        16 => {
+                            let prev = *lengths.get(i.wrapping_sub(1)).ok_or_else(|| invalid("bad code lengths"))?;
+                            (prev, 3 + reader.bits(2)? as usize)
+                        }
+                        17 => (0, 3 + reader.bits(3)? as usize),
+                        _ => (0, 11 + reader.bits(7)? as usize),
+                    };
+                    if i + repeat > lengths.len() {
+                        return Err(invalid("bad code lengths"));
+                    }
+                    lengths[i..i + repeat].fill(value);
+                    i += repeat;
+                }
+
+                let lit = Huffman::new(&lengths[..hlit]);
+                let dist = Huffman::new(&lengths[hlit..]);
+                inflate_block(&mut reader, &mut out, &lit, &dist)?;
+            }
+            _ => return Err(invalid("bad deflate block type")),
+        }
+
+        if is_final {
+            return Ok(out);
+        }
+    }
+}
+
+fn inflate_block(reader: &mut BitReader, out: &mut Vec<u8>, lit: &Huffman, dist: &Huffman) -> io::Result<()> {
+    loop {
+        let symbol = lit.decode(reader)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let i = symbol - 257;
+                if i >= LENGTH_BASE.len() {
+                    return Err(invalid("bad length symbol"));
+                }
+                let len = LENGTH_BASE[i] as usize + reader.bits(LENGTH_EXTRA[i] as u32)? as usize;
+
+                let d = dist.decode(reader)? as usize;
+                if d >= DIST_BASE.len() {
+                    return Err(invalid("bad distance symbol"));
+                }
+                let distance = DIST_BASE[d] as usize + reader.bits(DIST_EXTRA[d] as u32)? as usize;
+                if distance > out.len() {
+                    return Err(invalid("distance too far back"));
+                }
+
+                let start = out.len() - distance;
+                for k in 0..len {
+                    out.push(out[start + k]);
+                }
+            }
+        }
+    }
+}
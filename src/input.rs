@@ -0,0 +1,47 @@
+use minifb::{ Key, MouseButton };
+
+/// Snapshot of the input for a single frame. `Drawer` only ever reads from
+/// this, so it can be filled from a window, a script or left empty when
+/// rendering headless.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputState {
+    /// Keys held down during this frame.
+    pub keys_down: Vec<Key>,
+    /// Keys that went down this frame (no key repeat).
+    pub keys_pressed: Vec<Key>,
+    /// Mouse position in window pixels, `None` when outside the window.
+    pub mouse_position: Option<(f32, f32)>,
+    /// Mouse buttons held down during this frame.
+    pub mouse_down: Vec<MouseButton>,
+    /// Vertical wheel movement this frame, positive away from the user.
+    pub scroll: f32,
+}
+
+impl InputState {
+    pub fn is_key_down(&self, key: Key) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    pub fn is_key_pressed(&self, key: Key) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    pub fn is_mouse_down(&self, button: MouseButton) -> bool {
+        self.mouse_down.contains(&button)
+    }
+}
+
+/// Anything that can provide an `InputState` once per frame.
+pub trait InputSource {
+    fn poll(&mut self) -> InputState;
+}
+
+/// Input source that never reports anything, for headless rendering.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoInput;
+
+impl InputSource for NoInput {
+    fn poll(&mut self) -> InputState {
+        InputState::default()
+    }
+}
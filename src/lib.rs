@@ -0,0 +1,15 @@
+#![allow(dead_code)]
+
+pub mod math;
+pub mod camera;
+pub mod drawer;
+pub mod frame_buffer;
+pub mod image_export;
+pub mod image_import;
+pub mod input;
+pub mod light;
+pub mod presenter;
+pub mod raster;
+pub mod scene;
+pub mod shader;
+pub mod texture;
@@ -0,0 +1,150 @@
+use crate::math::{color::Color, matrix4::Mat4, vector4f::Vec4F};
+
+/// How a point or spot light fades with distance `d`:
+/// `1 / (constant + linear * d + quadratic * d * d)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Default for Attenuation {
+    /// Fades out over roughly 50 units.
+    fn default() -> Self {
+        Attenuation { constant: 1.0, linear: 0.09, quadratic: 0.032 }
+    }
+}
+
+impl Attenuation {
+    /// No falloff at all.
+    pub const NONE: Attenuation = Attenuation { constant: 1.0, linear: 0.0, quadratic: 0.0 };
+
+    pub fn factor(&self, distance: f32) -> f32 {
+        1.0 / (self.constant + self.linear * distance + self.quadratic * distance * distance).max(f32::EPSILON)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LightKind {
+    /// Parallel rays, like the sun. `direction` is where the light travels.
+    Directional { direction: Vec4F },
+    /// Shines in every direction from `position`.
+    Point { position: Vec4F, attenuation: Attenuation },
+    /// A point light limited to a cone around `direction`. Full strength up
+    /// to `inner_angle`, fading to nothing at `outer_angle`; both are
+    /// measured from the axis, in radians.
+    Spot {
+        position: Vec4F,
+        direction: Vec4F,
+        inner_angle: f32,
+        outer_angle: f32,
+        attenuation: Attenuation,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: Color,
+}
+
+impl Light {
+    pub fn directional(mut direction: Vec4F, color: Color) -> Light {
+        Light { kind: LightKind::Directional { direction: direction.normalize() }, color }
+    }
+
+    pub fn point(position: Vec4F, color: Color) -> Light {
+        Light { kind: LightKind::Point { position, attenuation: Attenuation::default() }, color }
+    }
+
+    pub fn spot(position: Vec4F, mut direction: Vec4F, inner_angle: f32, outer_angle: f32, color: Color) -> Light {
+        Light {
+            kind: LightKind::Spot {
+                position,
+                direction: direction.normalize(),
+                inner_angle,
+                outer_angle,
+                attenuation: Attenuation::default(),
+            },
+            color,
+        }
+    }
+
+    /// Moves and turns the light, e.g. by a rotation about the origin.
+    pub fn transform(&mut self, mat: Mat4) {
+        let turn = |direction: &mut Vec4F| *direction = (mat * Vec4F { w: 0.0, ..*direction }).normalize();
+        match &mut self.kind {
+            LightKind::Directional { direction } => turn(direction),
+            LightKind::Point { position, .. } => *position = mat * *position,
+            LightKind::Spot { position, direction, .. } => {
+                *position = mat * *position;
+                turn(direction);
+            }
+        }
+    }
+
+    /// Unit vector from `point` towards the light and the light arriving
+    /// there, or `None` when `point` is outside a spot light's cone.
+    pub fn incoming(&self, point: &Vec4F) -> Option<(Vec4F, Color)> {
+        match self.kind {
+            LightKind::Directional { direction } => Some((direction * -1.0, self.color)),
+            LightKind::Point { position, attenuation } => {
+                let mut to_light = position - *point;
+                let distance = to_light.length();
+                Some((to_light.normalize(), self.color * attenuation.factor(distance)))
+            }
+            LightKind::Spot { position, direction, inner_angle, outer_angle, attenuation } => {
+                let mut to_light = position - *point;
+                let distance = to_light.length();
+                let to_light = to_light.normalize();
+
+                let cos_angle = -to_light.dot_product(&direction);
+                let (cos_inner, cos_outer) = (inner_angle.cos(), outer_angle.cos());
+                if cos_angle <= cos_outer {
+                    return None;
+                }
+                let cone = ((cos_angle - cos_outer) / (cos_inner - cos_outer).max(f32::EPSILON)).min(1.0);
+
+                Some((to_light, self.color * (cone * attenuation.factor(distance))))
+            }
+        }
+    }
+}
+
+/// What the lighting needs to know about a surface.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Surface {
+    pub diffuse: Color,
+    pub specular: Color,
+    /// Blinn-Phong exponent; 0 disables the highlight.
+    pub shininess: f32,
+}
+
+/// Blinn-Phong lighting of one point. The ambient term uses the diffuse
+/// color, since most exported materials leave `Ka` black. `normal` must be
+/// unit length.
+pub fn shade(lights: &[Light], ambient: Color, eye: &Vec4F, surface: &Surface, point: &Vec4F, normal: &Vec4F) -> Color {
+    let mut color = surface.diffuse * ambient;
+    let mut to_eye = *eye - *point;
+    let to_eye = to_eye.normalize();
+
+    for light in lights {
+        let Some((to_light, incoming)) = light.incoming(point) else { continue };
+        let lambert = normal.dot_product(&to_light);
+        if lambert <= 0.0 {
+            continue;
+        }
+        color += surface.diffuse * incoming * lambert;
+
+        if surface.shininess > 0.0 {
+            let mut halfway = to_light + to_eye;
+            if halfway.length() > 0.0 {
+                let highlight = normal.dot_product(&halfway.normalize()).max(0.0).powf(surface.shininess);
+                color += surface.specular * incoming * highlight;
+            }
+        }
+    }
+
+    color
+}
@@ -1,87 +1,121 @@
-#![allow(dead_code)]
-use std::cell::RefCell;
-use std::rc::Rc;
-use std::time::{Duration, Instant};
+use std::env;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use drawer::Drawer;
-use minifb::{Key, Scale, ScaleMode, Window, WindowOptions};
-
-pub mod math;
-pub mod camera;
-mod drawer;
+use minifb::Key;
+use testy_rusty::drawer::Drawer;
+use testy_rusty::image_export::save_image;
+use testy_rusty::input::InputSource;
+use testy_rusty::presenter::{Presenter, WindowPresenter};
+use testy_rusty::scene::Transform;
 
 const SCREEN_WIDTH: usize = 1920;
 const SCREEN_HEIGHT: usize = 1080;
+const DEFAULT_MODEL: &str = "src/objects/teatpot.obj";
 
 fn main() {
-    let window = Rc::new(RefCell::new(setup_window(SCREEN_WIDTH, SCREEN_HEIGHT)));
-
-    window.borrow_mut().set_position(-10, 0);
+    let mut presenter = WindowPresenter::new("Tests", SCREEN_WIDTH, SCREEN_HEIGHT)
+        .unwrap_or_else(|e| panic!("{}", e));
 
-    let mut drawer = Drawer::new(SCREEN_WIDTH, SCREEN_HEIGHT, window.clone());
+    presenter.window.set_position(-10, 0);
 
-    drawer.ready();
+    let mut drawer = Drawer::new(SCREEN_WIDTH, SCREEN_HEIGHT);
 
-    let mut is_open = window.borrow().is_open();
-    let mut is_down = window.borrow().is_key_down(Key::Escape);
-
-    let (near, far, fov_deg, aspect_ratio) = (drawer.camera.near, drawer.camera.far, drawer.camera.fov, drawer.camera.aspect_ratio);
+    // Every model given on the command line goes into the scene, side by side.
+    let mut model_paths: Vec<String> = env::args().skip(1).collect();
+    if model_paths.is_empty() {
+        model_paths.push(DEFAULT_MODEL.to_string());
+    }
+    if let Err(e) = drawer.ready(&model_paths[0]) {
+        eprintln!("Cannot load model: {}", e);
+        std::process::exit(1);
+    }
+    for (i, path) in model_paths.iter().enumerate().skip(1) {
+        match drawer.scene.load_mesh(path) {
+            Ok(mesh) => {
+                drawer.scene.add_mesh_node(path, mesh, Transform::from_translation(6.0 * i as f32, 0.0, 5.0));
+            }
+            Err(e) => {
+                eprintln!("Cannot load model: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    for warning in drawer.scene.warnings() {
+        eprintln!("Warning: {}", warning);
+    }
 
     let mut last_instant = Instant::now();
 
-    window
-        .borrow_mut()
+    presenter
+        .window
         .limit_update_rate(
             Option::from(
                 Duration::from_micros(16666)
             )
         );
 
-    while is_open && !is_down {
+    while presenter.is_open() && !presenter.window.is_key_down(Key::Escape) {
         let now = Instant::now();
         let delta = now.duration_since(last_instant).as_secs_f32();
         last_instant = now;
         let fps = 1.0 / delta;
 
-        drawer.update(delta);
+        let (width, height) = presenter.size();
+        if width > 0 && height > 0 && (width, height) != drawer.output_size() {
+            drawer.resize(width, height);
+        }
 
-        draw_debug(&mut drawer, near, far, fov_deg, aspect_ratio, delta, fps);
+        let input = presenter.poll();
+        drawer.update(delta, &input);
 
-        window
-            .borrow_mut()
-            .update_with_buffer(drawer.buffer.as_slice(), drawer.width, drawer.height)
-            .unwrap();
+        draw_debug(&mut drawer, delta, fps);
 
-        is_open = window.borrow().is_open();
-        is_down = window.borrow().is_key_down(Key::Escape);
+        if input.is_key_pressed(Key::F12) {
+            take_screenshot(&drawer);
+        }
+
+        presenter.present(&drawer.target).unwrap();
+    }
+}
+
+fn take_screenshot(drawer: &Drawer) {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let path = format!("screenshot_{}.png", stamp);
+
+    match save_image(&drawer.target, &path) {
+        Ok(()) => println!("Saved {}", path),
+        Err(e) => eprintln!("Cannot save {}: {}", path, e),
     }
 }
 
-fn draw_debug(
-    drawer: &mut Drawer,
-    near: f32,
-    far: f32,
-    fov_deg: f32,
-    aspect_ratio: f32,
-    delta: f32,
-    fps: f32,
-) {
-    drawer.draw_string(10, 10, format!("NEAR: {}", near).as_str(), 0xFFFFFF);
-    drawer.draw_string(10, 25, format!("FAR: {}", far).as_str(), 0xFFFFFF);
-    drawer.draw_string(10, 40, format!("FOV: {}", fov_deg).as_str(), 0xFFFFFF);
+fn draw_debug(drawer: &mut Drawer, delta: f32, fps: f32) {
+    let camera = drawer.camera;
+    let (yaw, pitch, roll) = camera.yaw_pitch_roll();
+    drawer.draw_string(10, 10, format!("NEAR: {}", camera.near).as_str(), 0xFFFFFF);
+    drawer.draw_string(10, 25, format!("FAR: {}", camera.far).as_str(), 0xFFFFFF);
+    drawer.draw_string(10, 40, format!("FOV: {}", camera.fov).as_str(), 0xFFFFFF);
     drawer.draw_string(
         10,
         55,
-        format!("ASPECT RATIO: {}", aspect_ratio).as_str(),
+        format!("ASPECT RATIO: {}", camera.aspect_ratio).as_str(),
         0xFFFFFF,
     );
     drawer.draw_string(10, 70, format!("DELTA: {}", delta).as_str(), 0xFFFFFF);
     drawer.draw_string(10, 85, format!("FPS: {:.2}", fps).as_str(), 0xFFFFFF);
-    drawer.draw_string(10, 115, format!("YAW: {}", drawer.camera.yaw).as_str(), 0xFFFFFF);
+    drawer.draw_string(
+        10,
+        100,
+        format!("TRIANGLES: {}", drawer.triangles_drawn).as_str(),
+        0xFFFFFF,
+    );
+    drawer.draw_string(10, 115, format!("YAW: {}", yaw).as_str(), 0xFFFFFF);
     drawer.draw_string(
         10,
         130,
-        format!("PITCH: {}", drawer.camera.pitch).as_str(),
+        format!("PITCH: {}", pitch).as_str(),
         0xFFFFFF,
     );
     drawer.draw_string(
@@ -90,19 +124,61 @@ fn draw_debug(
         format!("LOOK DIR: {}", drawer.camera.look_dir).as_str(),
         0xFFFFFF,
     );
-}
-
-fn setup_window(width: usize, height: usize) -> Window {
-    Window::new(
-        "Tests",
-        width,
-        height,
-        WindowOptions {
-            resize: true,
-            scale: Scale::FitScreen,
-            scale_mode: ScaleMode::AspectRatioStretch,
-            ..WindowOptions::default()
-        },
-    )
-    .unwrap_or_else(|e| panic!("{}", e))
+    drawer.draw_string(
+        10,
+        160,
+        format!("DEPTH: {:?}", drawer.depth_mode).as_str(),
+        0xFFFFFF,
+    );
+    drawer.draw_string(
+        10,
+        175,
+        format!("COLORS: {:?}", drawer.color_style).as_str(),
+        0xFFFFFF,
+    );
+    drawer.draw_string(
+        10,
+        190,
+        format!("SHADING: {:?}", drawer.shading_mode).as_str(),
+        0xFFFFFF,
+    );
+    drawer.draw_string(10, 205, format!("ROLL: {}", roll).as_str(), 0xFFFFFF);
+    drawer.draw_string(
+        10,
+        220,
+        format!("MOUSE LOOK: {}", if drawer.mouse_look.enabled { "ON" } else { "OFF" }).as_str(),
+        0xFFFFFF,
+    );
+    drawer.draw_string(
+        10,
+        235,
+        format!("CAMERA: {:?}", drawer.camera_mode).as_str(),
+        0xFFFFFF,
+    );
+    drawer.draw_string(
+        10,
+        250,
+        format!("PROJECTION: {:?} x{:.2}", drawer.camera.projection, drawer.camera.zoom).as_str(),
+        0xFFFFFF,
+    );
+    drawer.draw_string(
+        10,
+        265,
+        format!(
+            "RESOLUTION: {}x{} (x{})",
+            drawer.target.width,
+            drawer.target.height,
+            drawer.render_scale()
+        )
+        .as_str(),
+        0xFFFFFF,
+    );
+    drawer.draw_string(
+        10,
+        280,
+        format!("RENDER MODE: {:?}", drawer.render_mode).as_str(),
+        0xFFFFFF,
+    );
+    let cull = drawer.cull_mode.map_or("PER MESH".to_string(), |mode| format!("{:?}", mode));
+    drawer.draw_string(10, 295, format!("CULL: {}", cull).as_str(), 0xFFFFFF);
 }
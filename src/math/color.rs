@@ -0,0 +1,110 @@
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Sub};
+
+/// Linear RGB color with channels nominally in 0.0..=1.0. Values may go
+/// outside that range while lighting is accumulated; `to_u32` clamps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Color::BLACK
+    }
+}
+
+impl Color {
+    pub const BLACK: Color = Color { r: 0.0, g: 0.0, b: 0.0 };
+    pub const WHITE: Color = Color { r: 1.0, g: 1.0, b: 1.0 };
+
+    pub fn new(r: f32, g: f32, b: f32) -> Color {
+        Color { r, g, b }
+    }
+
+    pub fn gray(value: f32) -> Color {
+        Color { r: value, g: value, b: value }
+    }
+
+    /// Unpacks a 0x00RRGGBB value.
+    pub fn from_u32(col: u32) -> Color {
+        Color {
+            r: ((col >> 16) & 0xFF) as f32 / 255.0,
+            g: ((col >> 8) & 0xFF) as f32 / 255.0,
+            b: (col & 0xFF) as f32 / 255.0,
+        }
+    }
+
+    /// Packs into 0x00RRGGBB, clamping every channel first.
+    pub fn to_u32(&self) -> u32 {
+        let c = self.clamp();
+        let channel = |v: f32| (v * 255.0).round() as u32;
+
+        (channel(c.r) << 16) | (channel(c.g) << 8) | channel(c.b)
+    }
+
+    pub fn clamp(&self) -> Color {
+        Color {
+            r: self.r.clamp(0.0, 1.0),
+            g: self.g.clamp(0.0, 1.0),
+            b: self.b.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn lerp(&self, other: &Color, t: f32) -> Color {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Add for Color {
+    type Output = Color;
+    fn add(self, rhs: Self) -> Self::Output {
+        Color { r: self.r + rhs.r, g: self.g + rhs.g, b: self.b + rhs.b }
+    }
+}
+
+impl Sub for Color {
+    type Output = Color;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Color { r: self.r - rhs.r, g: self.g - rhs.g, b: self.b - rhs.b }
+    }
+}
+
+impl AddAssign for Color {
+    fn add_assign(&mut self, rhs: Self) {
+        self.r += rhs.r;
+        self.g += rhs.g;
+        self.b += rhs.b;
+    }
+}
+
+/// Channel-wise product, e.g. a surface color lit by a colored light.
+impl Mul for Color {
+    type Output = Color;
+    fn mul(self, rhs: Self) -> Self::Output {
+        Color { r: self.r * rhs.r, g: self.g * rhs.g, b: self.b * rhs.b }
+    }
+}
+
+impl Mul<f32> for Color {
+    type Output = Color;
+    fn mul(self, rhs: f32) -> Self::Output {
+        Color { r: self.r * rhs, g: self.g * rhs, b: self.b * rhs }
+    }
+}
+
+impl MulAssign<f32> for Color {
+    fn mul_assign(&mut self, rhs: f32) {
+        self.r *= rhs;
+        self.g *= rhs;
+        self.b *= rhs;
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.3}, {:.3}, {:.3}", self.r, self.g, self.b)
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::math::color::Color;
+use crate::math::mesh::{statements, tokens, CullMode, MeshLoadError, MeshLoadErrorKind, SourcePos};
+use crate::texture::Texture;
+
+/// Surface description from an MTL file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+    pub name: String,
+    /// `Ka`
+    pub ambient: Color,
+    /// `Kd`
+    pub diffuse: Color,
+    /// `Ks`
+    pub specular: Color,
+    /// `Ns`, the specular exponent.
+    pub shininess: f32,
+    /// `d`, 1.0 is fully opaque. `Tr` is read as `1 - d`.
+    pub dissolve: f32,
+    /// `map_Kd`, replaces `diffuse` where set.
+    pub diffuse_map: Option<Arc<Texture>>,
+    /// `map_Bump` / `bump`
+    pub bump_map: Option<Arc<Texture>>,
+    /// Overrides `Mesh::cull_mode` for faces with this material, and is in
+    /// turn overridden by `Drawer::cull_mode`. Not part of MTL, so only set
+    /// from code.
+    pub cull_mode: Option<CullMode>,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            name: String::new(),
+            ambient: Color::BLACK,
+            diffuse: Color::WHITE,
+            specular: Color::BLACK,
+            shininess: 0.0,
+            dissolve: 1.0,
+            diffuse_map: None,
+            bump_map: None,
+            cull_mode: None,
+        }
+    }
+}
+
+impl Material {
+    /// Loads every material in an MTL file. Texture maps are resolved next
+    /// to the file; maps that cannot be loaded are reported in `warnings`
+    /// and left unset.
+    pub fn parse_mtl_file(filename: &str, warnings: &mut Vec<String>) -> Result<Vec<Material>, MeshLoadError> {
+        let source = fs::read_to_string(Path::new(filename))
+            .map_err(|e| MeshLoadError::new(filename, 0, 0, MeshLoadErrorKind::Io(e)))?;
+
+        Self::parse_mtl_str(&source, filename, warnings)
+    }
+
+    pub fn parse_mtl_str(source: &str, filename: &str, warnings: &mut Vec<String>) -> Result<Vec<Material>, MeshLoadError> {
+        let base_dir = Path::new(filename).parent().unwrap_or(Path::new(""));
+        let mut pos = SourcePos { filename, line: 0 };
+        let mut materials: Vec<Material> = Vec::new();
+        let mut textures: HashMap<PathBuf, Option<Arc<Texture>>> = HashMap::new();
+
+        for (line_number, line) in statements(source) {
+            pos.line = line_number;
+
+            let end = line.chars().count() + 1;
+            let mut parts = tokens(&line);
+            let Some((column, keyword)) = parts.next() else { continue };
+
+            if keyword == "newmtl" {
+                let name = parts.map(|(_, token)| token).collect::<Vec<&str>>().join(" ");
+                if name.is_empty() {
+                    return Err(pos.error(end, MeshLoadErrorKind::MissingName("material")));
+                }
+                materials.push(Material { name, ..Material::default() });
+                continue;
+            }
+
+            // Statements before the first `newmtl` have nothing to apply to.
+            let Some(material) = materials.last_mut() else { continue };
+
+            match keyword {
+                "Ka" | "Kd" | "Ks" => {
+                    // Spectral curves and CIE XYZ colors are skipped rather
+                    // than failing the whole load; the color stays as it was.
+                    if let Some((column, form @ ("spectral" | "xyz"))) = tokens(&line).nth(1) {
+                        warnings.push(format!(
+                            "{}:{}:{}: unsupported {} color for {}",
+                            filename, line_number, column, form, keyword
+                        ));
+                        continue;
+                    }
+                    let mut rgb = [0.0; 3];
+                    pos.parse_floats(&mut parts, end, &mut rgb, 1, "color component")?;
+                    // A single value means gray.
+                    if line.split_whitespace().count() == 2 {
+                        rgb = [rgb[0]; 3];
+                    }
+                    let color = Color::new(rgb[0], rgb[1], rgb[2]);
+                    match keyword {
+                        "Ka" => material.ambient = color,
+                        "Kd" => material.diffuse = color,
+                        _ => material.specular = color,
+                    }
+                }
+                "Ns" => {
+                    let mut value = [0.0];
+                    pos.parse_floats(&mut parts, end, &mut value, 1, "specular exponent")?;
+                    material.shininess = value[0];
+                }
+                "d" | "Tr" => {
+                    let mut value = [1.0];
+                    pos.parse_floats(&mut parts, end, &mut value, 1, "dissolve")?;
+                    material.dissolve = if keyword == "d" { value[0] } else { 1.0 - value[0] };
+                }
+                "map_Kd" | "map_Bump" | "map_bump" | "bump" => {
+                    // Options like `-bm 1.0` come first, the file name is last.
+                    let file = parts
+                        .last()
+                        .map(|(_, token)| token)
+                        .ok_or_else(|| pos.error(end, MeshLoadErrorKind::MissingName("texture")))?;
+                    let path = base_dir.join(file);
+
+                    let texture = textures
+                        .entry(path.clone())
+                        .or_insert_with(|| match Texture::load(&path) {
+                            Ok(texture) => Some(Arc::new(texture)),
+                            Err(e) => {
+                                warnings.push(format!(
+                                    "{}:{}:{}: cannot load {}: {}",
+                                    filename, line_number, column, path.display(), e
+                                ));
+                                None
+                            }
+                        })
+                        .clone();
+
+                    if keyword == "map_Kd" {
+                        material.diffuse_map = texture;
+                    } else {
+                        material.bump_map = texture;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(materials)
+    }
+}
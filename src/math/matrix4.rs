@@ -2,7 +2,7 @@ use std::ops::{Add, Mul, Sub};
 use std::default::Default;
 use crate::math::vector4f::Vec4F;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Mat4 {
     pub m: [[f32; 4]; 4]
 }
@@ -27,7 +27,7 @@ impl Mat4 {
         }
     }
 
-    pub fn rotate_x(&self, angle: f32) -> Mat4 {
+    pub fn rotate_x(angle: f32) -> Mat4 {
         let mut mat = Mat4::default();
         mat.m[0][0] = 1.0_f32;
         mat.m[1][1] = angle.cos();
@@ -39,7 +39,7 @@ impl Mat4 {
         mat
     }
 
-    pub fn rotate_y(&self, angle: f32) -> Mat4 {
+    pub fn rotate_y(angle: f32) -> Mat4 {
         let mut mat = Mat4::default();
         mat.m[0][0] = angle.cos();
         mat.m[0][2] = angle.sin();
@@ -51,7 +51,7 @@ impl Mat4 {
         mat
     }
 
-    pub fn rotate_z(&self, angle: f32) -> Mat4 {
+    pub fn rotate_z(angle: f32) -> Mat4 {
         let mut mat = Mat4::default();
         mat.m[0][0] = angle.cos();
         mat.m[0][1] = angle.sin();
@@ -63,7 +63,7 @@ impl Mat4 {
         mat
     }
 
-    pub fn translate(&self, x: f32, y: f32, z: f32) -> Mat4 {
+    pub fn translate(x: f32, y: f32, z: f32) -> Mat4 {
         let mut mat = Mat4::default();
         mat.m[0][0] = 1.0_f32;
         mat.m[1][1] = 1.0_f32;
@@ -76,6 +76,43 @@ impl Mat4 {
         mat
     }
 
+    pub fn from_translation(offset: Vec4F) -> Mat4 {
+        Mat4::translate(offset.x, offset.y, offset.z)
+    }
+
+    pub fn scale(x: f32, y: f32, z: f32) -> Mat4 {
+        let mut mat = Mat4::default();
+        mat.m[0][0] = x;
+        mat.m[1][1] = y;
+        mat.m[2][2] = z;
+        mat.m[3][3] = 1.0_f32;
+
+        mat
+    }
+
+    /// Rotation by `angle` radians about `axis`, turning the same way as
+    /// `rotate_x` and `rotate_z` do about theirs. `rotate_y` turns the other
+    /// way, which the camera's yaw relies on. `axis` need not be unit length.
+    pub fn from_axis_angle(mut axis: Vec4F, angle: f32) -> Mat4 {
+        let a = axis.normalize();
+        let (s, c) = angle.sin_cos();
+        let t = 1.0_f32 - c;
+
+        let mut mat = Mat4::default();
+        mat.m[0][0] = c + t * a.x * a.x;
+        mat.m[0][1] = t * a.x * a.y + s * a.z;
+        mat.m[0][2] = t * a.x * a.z - s * a.y;
+        mat.m[1][0] = t * a.x * a.y - s * a.z;
+        mat.m[1][1] = c + t * a.y * a.y;
+        mat.m[1][2] = t * a.y * a.z + s * a.x;
+        mat.m[2][0] = t * a.x * a.z + s * a.y;
+        mat.m[2][1] = t * a.y * a.z - s * a.x;
+        mat.m[2][2] = c + t * a.z * a.z;
+        mat.m[3][3] = 1.0_f32;
+
+        mat
+    }
+
     pub fn project(fov_deg: f32, aspect_ratio: f32, near: f32, far: f32) -> Mat4 {
         let fov_rad = 1.0_f32 / (fov_deg * 0.5_f32 / 180.0_f32 * std::f32::consts::PI).tan();
         let mut mat: Mat4 = Mat4::default();
@@ -90,6 +127,40 @@ impl Mat4 {
         mat
     }
 
+    /// Perspective projection of the frustum whose near face spans `left` to
+    /// `right` and `bottom` to `top` in view space. Unlike `project` it need
+    /// not be centered on the view axis. Depth maps `near..far` to `0..1`.
+    pub fn frustum(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Mat4 {
+        let mut mat = Mat4::default();
+
+        mat.m[0][0] = 2.0_f32 * near / (right - left);
+        mat.m[1][1] = 2.0_f32 * near / (top - bottom);
+        mat.m[2][0] = -(right + left) / (right - left);
+        mat.m[2][1] = -(top + bottom) / (top - bottom);
+        mat.m[2][2] = far / (far - near);
+        mat.m[3][2] = -(far * near) / (far - near);
+        mat.m[2][3] = 1.0_f32;
+
+        mat
+    }
+
+    /// Parallel projection of the box from `left`, `bottom`, `near` to
+    /// `right`, `top`, `far` in view space. `w` stays 1, and depth maps
+    /// `near..far` to `0..1` like the perspective projections.
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Mat4 {
+        let mut mat = Mat4::default();
+
+        mat.m[0][0] = 2.0_f32 / (right - left);
+        mat.m[1][1] = 2.0_f32 / (top - bottom);
+        mat.m[2][2] = 1.0_f32 / (far - near);
+        mat.m[3][0] = -(right + left) / (right - left);
+        mat.m[3][1] = -(top + bottom) / (top - bottom);
+        mat.m[3][2] = -near / (far - near);
+        mat.m[3][3] = 1.0_f32;
+
+        mat
+    }
+
     pub fn point_at(pos: Vec4F, target: Vec4F, up: Vec4F) -> Mat4 {
         let new_forward = (target - pos).normalize();
 
@@ -120,6 +191,54 @@ impl Mat4 {
         mat
     }
 
+    /// Determinant of the 3x3 matrix left after removing `row` and `col`.
+    fn minor(&self, row: usize, col: usize) -> f32 {
+        let mut m = [[0.0_f32; 3]; 3];
+        for (i, src_row) in (0..4).filter(|&r| r != row).enumerate() {
+            for (j, src_col) in (0..4).filter(|&c| c != col).enumerate() {
+                m[i][j] = self.m[src_row][src_col];
+            }
+        }
+
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    fn cofactor(&self, row: usize, col: usize) -> f32 {
+        let sign = if (row + col) & 1 == 0 { 1.0_f32 } else { -1.0_f32 };
+        sign * self.minor(row, col)
+    }
+
+    pub fn determinant(&self) -> f32 {
+        (0..4).map(|col| self.m[0][col] * self.cofactor(0, col)).sum()
+    }
+
+    /// Inverse of any invertible matrix, `None` when the determinant is
+    /// (nearly) zero. Prefer `quick_inverse` for rotation plus translation.
+    pub fn inverse(&self) -> Option<Mat4> {
+        let det = self.determinant();
+        if det.abs() < f32::EPSILON * f32::EPSILON {
+            return None;
+        }
+
+        let mut mat = Mat4::default();
+        for i in 0..4 {
+            for j in 0..4 {
+                // The adjugate is the transposed cofactor matrix.
+                mat.m[j][i] = self.cofactor(i, j) / det;
+            }
+        }
+
+        Some(mat)
+    }
+
+    /// Matrix for transforming normals, the inverse transpose. Keeps normals
+    /// perpendicular to their surface under non-uniform scale.
+    pub fn normal_matrix(&self) -> Mat4 {
+        self.inverse().map_or(*self, |inverse| inverse.transpose())
+    }
+
     pub fn transpose(&self) -> Mat4 {
         let mut mat = Mat4::default();
 
@@ -164,7 +283,7 @@ impl Add for Mat4 {
     type Output = Mat4;
 
     fn add(self, rhs: Self) -> Self::Output {
-        let mut mat = Mat4::default();
+        let mut mat = self;
 
         for i in 0..4 {
             for j in 0..4 {
@@ -180,7 +299,7 @@ impl Sub for Mat4 {
     type Output = Mat4;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        let mut mat = Mat4::default();
+        let mut mat = self;
 
         for i in 0..4 {
             for j in 0..4 {
@@ -1,123 +1,429 @@
-use std::fs::File;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
 use std::io;
-use std::io::BufRead;
 use std::path::Path;
-use crate::drawer::{Triangle, Vec4F};
+use crate::drawer::{Color, Triangle, Vec3F, Vec4F};
+use crate::math::material::Material;
 
+#[derive(Debug)]
+pub enum MeshLoadErrorKind {
+    Io(io::Error),
+    /// A statement ended before all of its values were read.
+    MissingValue(&'static str),
+    InvalidNumber(String),
+    /// Index that does not refer to an element read so far.
+    IndexOutOfRange { index: i64, count: usize },
+    /// A statement that needs an argument, like a file or material name.
+    MissingName(&'static str),
+}
+
+/// Error returned by the OBJ loader. `line` and `column` are 1-based and
+/// point at the offending token; both are 0 when the error is not tied to a
+/// position in the file (e.g. the file cannot be opened).
+#[derive(Debug)]
+pub struct MeshLoadError {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub kind: MeshLoadErrorKind,
+}
+
+impl MeshLoadError {
+    pub(crate) fn new(file: &str, line: usize, column: usize, kind: MeshLoadErrorKind) -> MeshLoadError {
+        MeshLoadError { file: file.to_string(), line, column, kind }
+    }
+}
+
+impl Display for MeshLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line > 0 {
+            write!(f, "{}:{}:{}: ", self.file, self.line, self.column)?;
+        } else {
+            write!(f, "{}: ", self.file)?;
+        }
+
+        match &self.kind {
+            MeshLoadErrorKind::Io(e) => write!(f, "{}", e),
+            MeshLoadErrorKind::MissingValue(what) => write!(f, "missing {}", what),
+            MeshLoadErrorKind::InvalidNumber(token) => write!(f, "invalid number '{}'", token),
+            MeshLoadErrorKind::IndexOutOfRange { index, count } => {
+                write!(f, "index {} out of range, {} elements defined", index, count)
+            }
+            MeshLoadErrorKind::MissingName(what) => write!(f, "missing {} name", what),
+        }
+    }
+}
+
+impl Error for MeshLoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            MeshLoadErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Named range of triangles started by an `o` or `g` statement.
 #[derive(Clone, Debug, PartialEq)]
-pub struct Mesh {
-    pub tris: Vec<Triangle>
+pub struct MeshGroup {
+    pub name: String,
+    /// Index of the first triangle in `Mesh::tris`.
+    pub start: usize,
+    /// One past the last triangle.
+    pub end: usize,
+}
+
+/// Splits a line into whitespace separated tokens with their 1-based column.
+pub(crate) fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace().map(move |token| {
+        let offset = token.as_ptr() as usize - line.as_ptr() as usize;
+        (line[..offset].chars().count() + 1, token)
+    })
 }
 
-impl Default for Mesh {
-    fn default() -> Self {
-        Mesh { tris: Vec::new() }
+/// Splits OBJ/MTL source into statements with their 1-based starting line.
+/// Lines ending in a backslash are joined with the next one and `#`
+/// comments are removed.
+pub(crate) fn statements(source: &str) -> Vec<(usize, String)> {
+    let mut out = Vec::new();
+    let mut lines = source.lines().enumerate();
+
+    while let Some((index, raw)) = lines.next() {
+        let mut line = raw.to_string();
+        while line.trim_end().ends_with('\\') {
+            let trimmed = line.trim_end().len() - 1;
+            line.truncate(trimmed);
+            line.push(' ');
+            match lines.next() {
+                Some((_, next)) => line.push_str(next),
+                None => break,
+            }
+        }
+
+        if let Some(comment) = line.find('#') {
+            line.truncate(comment);
+        }
+        out.push((index + 1, line));
     }
+
+    out
 }
 
-impl Mesh {
-    pub fn parse_obj_file(&mut self, filename: &str) -> Self {
-        let path = Path::new(filename);
-        let file = File::open(path).expect("Cannot open file");
-        let mut lines = io::BufReader::new(file).lines();
-
-        let mut major_ver: u32 = 0;
-
-        if let Some(Ok(line)) = lines.next() {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            let ver = parts[2];
-            let major_ver_str: Vec<&str> = if ver.starts_with('v') {
-                ver[1..].split('.').collect()
-            } else {
-                ver.split('.').collect()
-            };
-            major_ver = major_ver_str[0].parse::<u32>().unwrap();
+/// Where a parser currently is, for building errors and parsing numbers.
+#[derive(Default)]
+pub(crate) struct SourcePos<'a> {
+    pub filename: &'a str,
+    pub line: usize,
+}
+
+impl<'a> SourcePos<'a> {
+    pub fn error(&self, column: usize, kind: MeshLoadErrorKind) -> MeshLoadError {
+        MeshLoadError::new(self.filename, self.line, column, kind)
+    }
+
+    pub fn parse_f32(&self, column: usize, token: &str) -> Result<f32, MeshLoadError> {
+        token
+            .parse::<f32>()
+            .map_err(|_| self.error(column, MeshLoadErrorKind::InvalidNumber(token.to_string())))
+    }
+
+    /// Reads up to `out.len()` floats, failing if fewer than `required` are
+    /// present. Missing optional values keep their default.
+    pub fn parse_floats<'l>(
+        &self,
+        parts: &mut impl Iterator<Item = (usize, &'l str)>,
+        end: usize,
+        out: &mut [f32],
+        required: usize,
+        what: &'static str,
+    ) -> Result<(), MeshLoadError> {
+        for (i, value) in out.iter_mut().enumerate() {
+            match parts.next() {
+                Some((column, token)) => *value = self.parse_f32(column, token)?,
+                None if i < required => return Err(self.error(end, MeshLoadErrorKind::MissingValue(what))),
+                None => break,
+            }
         }
 
-        println!("{}", major_ver);
+        Ok(())
+    }
+}
+
+/// Vertex data collected while reading an OBJ file. Faces refer into these
+/// lists, so they are kept around until the whole file is read.
+#[derive(Default)]
+struct ObjParser<'a> {
+    pos: SourcePos<'a>,
+    positions: Vec<Vec4F>,
+    tex_coords: Vec<Vec3F>,
+    normals: Vec<Vec4F>,
+    /// Position indices of every triangle pushed so far, for computing the
+    /// normals a file leaves out.
+    face_positions: Vec<[usize; 3]>,
+}
+
+/// One corner of a face, with every index already resolved to 0-based.
+#[derive(Clone, Copy)]
+struct FaceVertex {
+    position: usize,
+    tex_coord: Option<usize>,
+    normal: Option<usize>,
+}
 
-        match major_ver {
-            2 => self.extract_model_from_obj2(filename),
-            4 => self.extract_model_from_obj4(filename),
-            _ => panic!("Cannot parse obj file: {}", filename),
+impl<'a> ObjParser<'a> {
+    fn error(&self, column: usize, kind: MeshLoadErrorKind) -> MeshLoadError {
+        self.pos.error(column, kind)
+    }
+
+    /// Resolves a 1-based or negative (relative to the end) OBJ index.
+    fn resolve_index(&self, column: usize, token: &str, count: usize) -> Result<usize, MeshLoadError> {
+        let index = token
+            .parse::<i64>()
+            .map_err(|_| self.error(column, MeshLoadErrorKind::InvalidNumber(token.to_string())))?;
+
+        let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+        if index == 0 || resolved < 0 || resolved >= count as i64 {
+            return Err(self.error(column, MeshLoadErrorKind::IndexOutOfRange { index, count }));
         }
+
+        Ok(resolved as usize)
     }
 
-    pub fn extract_model_from_obj4(&mut self, filename: &str) -> Self {
-        let path = Path::new(filename);
-        let file = File::open(path).expect("Cannot open file");
-        let reader = io::BufReader::new(file);
+    /// Parses one `v`, `v/vt`, `v//vn` or `v/vt/vn` face corner.
+    fn parse_face_vertex(&self, column: usize, token: &str) -> Result<FaceVertex, MeshLoadError> {
+        let mut indices = token.split('/');
+        let position = self.resolve_index(column, indices.next().unwrap_or(""), self.positions.len())?;
 
-        let mut vertices: Vec<Vec4F> = Vec::new();
-        for line in reader.lines().map(|line| line.unwrap()) {
-            let mut parts = line.split_whitespace();
-            if let Some(first) = parts.next() {
-                if first == "v" {
-                    let x = parts.next().unwrap().parse::<f32>().unwrap();
-                    let y = parts.next().unwrap().parse::<f32>().unwrap();
-                    let z = parts.next().unwrap().parse::<f32>().unwrap();
+        let tex_coord = match indices.next() {
+            Some("") | None => None,
+            Some(index) => Some(self.resolve_index(column, index, self.tex_coords.len())?),
+        };
+        let normal = match indices.next() {
+            Some("") | None => None,
+            Some(index) => Some(self.resolve_index(column, index, self.normals.len())?),
+        };
 
-                    let vec = Vec4F { x, y, z, ..Vec4F::default() };
-                    vertices.push(vec);
-                }
-                else if first == "f" {
-                    let first_numbers: Vec<i32> = parts.map(|part| part.split('/').next().unwrap().parse::<i32>().unwrap())
-                        .collect();
+        Ok(FaceVertex { position, tex_coord, normal })
+    }
+
+    fn parse(&mut self, source: &str) -> Result<Mesh, MeshLoadError> {
+        let mut mesh = Mesh::default();
+        let mut material: Option<usize> = None;
+        let base_dir = Path::new(self.pos.filename).parent().unwrap_or(Path::new(""));
+
+        for (line_number, line) in statements(source) {
+            self.pos.line = line_number;
 
+            let end = line.chars().count() + 1;
+            let mut parts = tokens(&line);
+            let Some((_, keyword)) = parts.next() else { continue };
 
-                    let tri = Triangle {
-                        p: [
-                            vertices[first_numbers[0] as usize - 1],
-                            vertices[first_numbers[1] as usize - 1],
-                            vertices[first_numbers[2] as usize - 1],
-                        ],
-                        color: 0xFFFFFF
-                    };
+            match keyword {
+                "v" => {
+                    let mut xyzw = [0.0, 0.0, 0.0, 1.0];
+                    self.pos.parse_floats(&mut parts, end, &mut xyzw, 3, "vertex coordinate")?;
+                    self.positions.push(Vec4F { x: xyzw[0], y: xyzw[1], z: xyzw[2], ..Vec4F::default() });
+                }
+                "vt" => {
+                    let mut uvw = [0.0, 0.0, 0.0];
+                    self.pos.parse_floats(&mut parts, end, &mut uvw, 1, "texture coordinate")?;
+                    self.tex_coords.push(Vec3F::new(uvw[0], uvw[1]));
+                }
+                "vn" => {
+                    let mut xyz = [0.0, 0.0, 0.0];
+                    self.pos.parse_floats(&mut parts, end, &mut xyz, 3, "normal component")?;
+                    let mut normal = Vec4F::new(xyz[0], xyz[1], xyz[2]);
+                    if normal.length() > 0.0 {
+                        normal = normal.normalize();
+                    }
+                    self.normals.push(normal);
+                }
+                "f" => {
+                    let corners = parts
+                        .map(|(column, token)| self.parse_face_vertex(column, token))
+                        .collect::<Result<Vec<FaceVertex>, MeshLoadError>>()?;
+                    if corners.len() < 3 {
+                        return Err(self.error(end, MeshLoadErrorKind::MissingValue("face vertex")));
+                    }
 
-                    self.tris.push(tri);
+                    // Fan triangulation, which is exact for the convex polygons
+                    // modelling tools export.
+                    for i in 1..corners.len() - 1 {
+                        let tri = [corners[0], corners[i], corners[i + 1]];
+                        mesh.tris.push(Triangle {
+                            p: tri.map(|corner| self.positions[corner.position]),
+                            t: tri.map(|corner| match corner.tex_coord {
+                                Some(index) => Vec3F::new(self.tex_coords[index].u, self.tex_coords[index].v),
+                                None => Vec3F::default(),
+                            }),
+                            // Zero marks a normal to compute once all faces are known.
+                            n: tri.map(|corner| match corner.normal {
+                                Some(index) => self.normals[index],
+                                None => Vec4F::new(0.0, 0.0, 0.0),
+                            }),
+                            color: Color::WHITE,
+                            material,
+                        });
+                        self.face_positions.push(tri.map(|corner| corner.position));
+                    }
+                }
+                "o" | "g" => {
+                    let name = parts.map(|(_, token)| token).collect::<Vec<&str>>().join(" ");
+                    mesh.start_group(name);
+                }
+                "mtllib" => {
+                    let files: Vec<&str> = parts.map(|(_, token)| token).collect();
+                    if files.is_empty() {
+                        return Err(self.error(end, MeshLoadErrorKind::MissingName("material library")));
+                    }
+                    for file in files {
+                        let path = base_dir.join(file);
+                        let path = path.to_string_lossy();
+                        match Material::parse_mtl_file(&path, &mut mesh.warnings) {
+                            Ok(materials) => mesh.materials.extend(materials),
+                            // A missing library only costs the colors, keep the geometry.
+                            Err(MeshLoadError { kind: MeshLoadErrorKind::Io(e), .. }) => {
+                                mesh.warnings.push(format!("{}: {}", path, e));
+                            }
+                            Err(e) => return Err(e),
+                        }
+                    }
                 }
+                "usemtl" => {
+                    let (column, name) = parts
+                        .next()
+                        .ok_or_else(|| self.error(end, MeshLoadErrorKind::MissingName("material")))?;
+                    material = mesh.materials.iter().rposition(|m| m.name == name);
+                    if material.is_none() {
+                        mesh.warnings.push(format!(
+                            "{}:{}:{}: unknown material '{}'",
+                            self.pos.filename, self.pos.line, column, name
+                        ));
+                    }
+                }
+                // Smoothing groups and everything else not needed for
+                // rendering are accepted and skipped.
+                _ => {}
             }
         }
 
-        self.clone()
+        if let Some(group) = mesh.groups.last_mut() {
+            group.end = mesh.tris.len();
+        }
+        self.fill_missing_normals(&mut mesh);
+
+        Ok(mesh)
     }
 
-    pub fn extract_model_from_obj2(&mut self, filename: &str) -> Self {
-        let path = Path::new(filename);
-        let file = File::open(path).expect("Cannot open file");
-        let reader = io::BufReader::new(file);
+    /// Gives every corner without a `vn` the area-weighted average of the
+    /// normals of all faces sharing its position. The unnormalized cross
+    /// product is twice the face area, so summing it does the weighting.
+    fn fill_missing_normals(&self, mesh: &mut Mesh) {
+        let zero = Vec4F::new(0.0, 0.0, 0.0);
+        if mesh.tris.iter().all(|tri| tri.n.iter().all(|n| *n != zero)) {
+            return;
+        }
+        mesh.computed_normals = true;
 
-        let mut vertices: Vec<Vec4F> = Vec::new();
-        for line in reader.lines().map(|line| line.unwrap()) {
-            let mut parts = line.split_whitespace();
-            if let Some(first) = parts.next() {
-                if first == "v" {
-                    let x = parts.next().unwrap().parse::<f32>().unwrap();
-                    let y = parts.next().unwrap().parse::<f32>().unwrap();
-                    let z = parts.next().unwrap().parse::<f32>().unwrap();
+        let mut sums = vec![zero; self.positions.len()];
+        for (tri, positions) in mesh.tris.iter().zip(&self.face_positions) {
+            let face = (tri.p[1] - tri.p[0]).cross_product(&(tri.p[2] - tri.p[0]));
+            for &position in positions {
+                sums[position] += face;
+            }
+        }
 
-                    let vec = Vec4F { x, y, z, ..Vec4F::default()};
-                    vertices.push(vec);
-                }
-                else if first == "f" {
-                    let a = parts.next().unwrap().parse::<i32>().unwrap();
-                    let b = parts.next().unwrap().parse::<i32>().unwrap();
-                    let c = parts.next().unwrap().parse::<i32>().unwrap();
-
-                    let tri = Triangle {
-                        p: [
-                            vertices[a as usize - 1],
-                            vertices[b as usize - 1],
-                            vertices[c as usize - 1],
-                        ],
-                        color: 0xFFFFFF
-                    };
-
-                    self.tris.push(tri);
+        for (tri, positions) in mesh.tris.iter_mut().zip(&self.face_positions) {
+            for (n, &position) in tri.n.iter_mut().zip(positions) {
+                let mut sum = sums[position];
+                if *n == zero && sum.length() > 0.0 {
+                    *n = sum.normalize();
                 }
             }
         }
+    }
+}
+
+/// Which faces `Drawer::render` leaves out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CullMode {
+    /// Faces turned away from the camera, for closed meshes.
+    #[default]
+    Back,
+    /// Faces turned towards the camera, showing the inside of a model.
+    Front,
+    /// None; faces seen from behind are lit as if flipped, so open meshes
+    /// look right from either side.
+    None,
+}
+
+/// The order in which a face's corners run around it, seen from its front.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Winding {
+    /// As OBJ and most exporters write them.
+    #[default]
+    CounterClockwise,
+    Clockwise,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    pub tris: Vec<Triangle>,
+    pub groups: Vec<MeshGroup>,
+    /// Materials from every `mtllib`, indexed by `Triangle::material`.
+    pub materials: Vec<Material>,
+    /// Problems that did not stop loading, like a missing material library.
+    pub warnings: Vec<String>,
+    /// Used for faces whose material does not set its own, unless
+    /// `Drawer::cull_mode` overrides both.
+    pub cull_mode: CullMode,
+    pub winding: Winding,
+    /// Set by the OBJ loader when it had to compute vertex normals. Those
+    /// point out of the counter-clockwise side of their faces, so `Drawer`
+    /// turns them around for clockwise meshes.
+    pub computed_normals: bool,
+}
+
+impl Mesh {
+    /// Loads a Wavefront OBJ file. Works for any exporter: faces may use any
+    /// of the `v/vt/vn` index forms, negative indices and polygons with more
+    /// than three vertices.
+    pub fn parse_obj_file(filename: &str) -> Result<Mesh, MeshLoadError> {
+        let source = fs::read_to_string(Path::new(filename))
+            .map_err(|e| MeshLoadError::new(filename, 0, 0, MeshLoadErrorKind::Io(e)))?;
+
+        Self::parse_obj_str(&source, filename)
+    }
+
+    /// Parses OBJ source held in memory. `filename` is used in errors and
+    /// to resolve `mtllib` paths.
+    pub fn parse_obj_str(source: &str, filename: &str) -> Result<Mesh, MeshLoadError> {
+        ObjParser { pos: SourcePos { filename, line: 0 }, ..ObjParser::default() }.parse(source)
+    }
 
-        self.clone()
+    /// Smallest and largest corner of the box around every vertex, `None`
+    /// for an empty mesh.
+    pub fn bounds(&self) -> Option<(Vec4F, Vec4F)> {
+        bounds_of(self.tris.iter().flat_map(|tri| tri.p))
     }
+
+    fn start_group(&mut self, name: String) {
+        let start = self.tris.len();
+        if let Some(group) = self.groups.last_mut() {
+            group.end = start;
+        }
+        self.groups.push(MeshGroup { name, start, end: start });
+    }
+}
+
+/// Axis-aligned box around `points`, `None` when there are none.
+pub fn bounds_of(points: impl IntoIterator<Item = Vec4F>) -> Option<(Vec4F, Vec4F)> {
+    points.into_iter().fold(None, |bounds, p| {
+        let (min, max) = bounds.unwrap_or((p, p));
+        Some((
+            Vec4F::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+            Vec4F::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+        ))
+    })
 }
@@ -2,3 +2,6 @@ pub mod vector4f;
 pub mod vector3f;
 pub mod matrix4;
 pub mod mesh;
+pub mod material;
+pub mod color;
+pub mod quat;
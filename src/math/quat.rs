@@ -0,0 +1,168 @@
+use std::fmt::Display;
+use std::ops::Mul;
+
+use crate::math::{matrix4::Mat4, vector4f::Vec4F};
+
+/// Rotation quaternion. Keep it unit length (see `normalize`) for the
+/// conversions to mean anything.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Quat::IDENTITY
+    }
+}
+
+/// `a * b` rotates by `b` first, then by `a`.
+impl Mul for Quat {
+    type Output = Quat;
+    fn mul(self, rhs: Self) -> Self::Output {
+        Quat {
+            x: self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            y: self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            z: self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            w: self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        }
+    }
+}
+
+impl Display for Quat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, {}, {}, {}", self.x, self.y, self.z, self.w)
+    }
+}
+
+impl Quat {
+    pub const IDENTITY: Quat = Quat { x: 0.0, y: 0.0, z: 0.0, w: 1.0 };
+
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Quat {
+        Quat { x, y, z, w }
+    }
+
+    /// Rotation by `angle` radians about `axis`, the same rotation as
+    /// `Mat4::from_axis_angle`. `axis` need not be unit length.
+    pub fn from_axis_angle(mut axis: Vec4F, angle: f32) -> Quat {
+        let axis = axis.normalize();
+        let (s, c) = (angle * 0.5).sin_cos();
+        Quat { x: axis.x * s, y: axis.y * s, z: axis.z * s, w: c }
+    }
+
+    /// Axis and angle in radians, the inverse of `from_axis_angle`. The
+    /// identity gives the x axis and zero.
+    pub fn to_axis_angle(&self) -> (Vec4F, f32) {
+        let q = self.normalize();
+        let s = (1.0 - q.w * q.w).max(0.0).sqrt();
+        if s < 1e-6 {
+            return (Vec4F::new(1.0, 0.0, 0.0), 0.0);
+        }
+
+        (Vec4F::new(q.x / s, q.y / s, q.z / s), 2.0 * q.w.clamp(-1.0, 1.0).acos())
+    }
+
+    /// Rotation about x by `x` radians, then about y by `y`, then about z by
+    /// `z`, all about the fixed world axes.
+    pub fn from_euler(x: f32, y: f32, z: f32) -> Quat {
+        let (sx, cx) = (x * 0.5).sin_cos();
+        let (sy, cy) = (y * 0.5).sin_cos();
+        let (sz, cz) = (z * 0.5).sin_cos();
+
+        Quat {
+            x: sx * cy * cz - cx * sy * sz,
+            y: cx * sy * cz + sx * cy * sz,
+            z: cx * cy * sz - sx * sy * cz,
+            w: cx * cy * cz + sx * sy * sz,
+        }
+    }
+
+    /// Angles for `from_euler` as `(x, y, z)`. `y` stays within ±π/2.
+    pub fn to_euler(&self) -> (f32, f32, f32) {
+        let q = self.normalize();
+        let x = (2.0 * (q.w * q.x + q.y * q.z)).atan2(1.0 - 2.0 * (q.x * q.x + q.y * q.y));
+        let y = (2.0 * (q.w * q.y - q.z * q.x)).clamp(-1.0, 1.0).asin();
+        let z = (2.0 * (q.w * q.z + q.x * q.y)).atan2(1.0 - 2.0 * (q.y * q.y + q.z * q.z));
+
+        (x, y, z)
+    }
+
+    pub fn dot(&self, other: &Quat) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Unit-length copy; a zero quaternion becomes the identity.
+    pub fn normalize(&self) -> Quat {
+        let l = self.length();
+        if l == 0.0 {
+            return Quat::IDENTITY;
+        }
+
+        Quat { x: self.x / l, y: self.y / l, z: self.z / l, w: self.w / l }
+    }
+
+    /// The opposite rotation, for unit quaternions.
+    pub fn conjugate(&self) -> Quat {
+        Quat { x: -self.x, y: -self.y, z: -self.z, w: self.w }
+    }
+
+    /// Spherical interpolation along the shorter arc: constant angular speed
+    /// from `self` at `t = 0` to `other` at `t = 1`.
+    pub fn slerp(&self, other: &Quat, t: f32) -> Quat {
+        let mut other = *other;
+        let mut cos_theta = self.dot(&other);
+        if cos_theta < 0.0 {
+            other = Quat { x: -other.x, y: -other.y, z: -other.z, w: -other.w };
+            cos_theta = -cos_theta;
+        }
+
+        // Nearly parallel: the sine below would divide by almost zero.
+        let (a, b) = if cos_theta > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (((1.0 - t) * theta).sin() / sin_theta, (t * theta).sin() / sin_theta)
+        };
+
+        Quat {
+            x: self.x * a + other.x * b,
+            y: self.y * a + other.y * b,
+            z: self.z * a + other.z * b,
+            w: self.w * a + other.w * b,
+        }
+        .normalize()
+    }
+
+    pub fn rotate(&self, v: Vec4F) -> Vec4F {
+        let p = Quat { x: v.x, y: v.y, z: v.z, w: 0.0 };
+        let r = *self * p * self.conjugate();
+        Vec4F { x: r.x, y: r.y, z: r.z, w: v.w }
+    }
+
+    pub fn to_mat4(&self) -> Mat4 {
+        let Quat { x, y, z, w } = *self;
+
+        // Laid out for `Mat4 * Vec4F`, which treats vectors as rows.
+        let mut mat = Mat4::default();
+        mat.m[0][0] = 1.0 - 2.0 * (y * y + z * z);
+        mat.m[0][1] = 2.0 * (x * y + w * z);
+        mat.m[0][2] = 2.0 * (x * z - w * y);
+        mat.m[1][0] = 2.0 * (x * y - w * z);
+        mat.m[1][1] = 1.0 - 2.0 * (x * x + z * z);
+        mat.m[1][2] = 2.0 * (y * z + w * x);
+        mat.m[2][0] = 2.0 * (x * z + w * y);
+        mat.m[2][1] = 2.0 * (y * z - w * x);
+        mat.m[2][2] = 1.0 - 2.0 * (x * x + y * y);
+        mat.m[3][3] = 1.0;
+
+        mat
+    }
+}
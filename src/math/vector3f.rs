@@ -1,5 +1,9 @@
 use std::fmt::Display;
 
+/// Texture coordinate: a `u`, `v` pair, with `w` left at 1. Projection does
+/// not touch it; the rasterizer interpolates it with perspective-correct
+/// weights.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Vec3F {
     pub u: f32,
     pub v: f32,
@@ -12,6 +16,20 @@ impl Default for Vec3F {
     }
 }
 
+impl Vec3F {
+    pub fn new(u: f32, v: f32) -> Vec3F {
+        Vec3F { u, v, ..Vec3F::default() }
+    }
+
+    pub fn lerp(&self, other: &Vec3F, t: f32) -> Vec3F {
+        Vec3F {
+            u: self.u + (other.u - self.u) * t,
+            v: self.v + (other.v - self.v) * t,
+            w: self.w + (other.w - self.w) * t,
+        }
+    }
+}
+
 impl Display for Vec3F {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}, {}, {}", self.u, self.v, self.w)
@@ -115,7 +115,7 @@ impl Vec4F {
     }
 
     pub fn length(&self) -> f32 {
-        (self.dot_product(&self)).sqrt()
+        (self.dot_product(self)).sqrt()
     }
 
     pub fn normalize(&mut self) -> Vec4F {
@@ -136,15 +136,14 @@ impl Vec4F {
         }
     }
 
-    pub fn intersects_plane(plane_p: &Vec4F, plane_n: &mut Vec4F, line_start: &Vec4F, line_end: &Vec4F) -> Vec4F {
-        *plane_n = plane_n.normalize();
-        let pd = -plane_n.dot_product(&plane_p);
-        let ad = line_start.dot_product(plane_n);
-        let bd = line_end.dot_product(plane_n);
-        let t = (-pd - ad) / (bd - ad);
-        let line_start_to_end = *line_end - *line_start;
-        let line_to_intersect = line_start_to_end * t;
-
-        *line_start + line_to_intersect
+    /// Interpolates all four components, `w` included, so it also works on
+    /// homogeneous clip-space positions.
+    pub fn lerp(&self, other: &Vec4F, t: f32) -> Vec4F {
+        Vec4F {
+            x: self.x + (other.x - self.x) * t,
+            y: self.y + (other.y - self.y) * t,
+            z: self.z + (other.z - self.z) * t,
+            w: self.w + (other.w - self.w) * t,
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,69 @@
+use minifb::{ KeyRepeat, MouseButton, MouseMode, Scale, ScaleMode, Window, WindowOptions };
+
+use crate::frame_buffer::FrameBuffer;
+use crate::input::{ InputSource, InputState };
+
+/// Something that shows a finished frame to the user.
+pub trait Presenter {
+    /// Frames of any size are stretched to fill the output.
+    fn present(&mut self, frame: &FrameBuffer) -> Result<(), String>;
+    fn is_open(&self) -> bool;
+    /// Current output size in pixels, which changes when the window is
+    /// resized.
+    fn size(&self) -> (usize, usize);
+}
+
+/// Presents frames in a minifb window and reads input from it.
+pub struct WindowPresenter {
+    pub window: Window,
+}
+
+impl WindowPresenter {
+    pub fn new(title: &str, width: usize, height: usize) -> Result<WindowPresenter, String> {
+        let window = Window::new(
+            title,
+            width,
+            height,
+            WindowOptions {
+                resize: true,
+                scale: Scale::FitScreen,
+                scale_mode: ScaleMode::AspectRatioStretch,
+                ..WindowOptions::default()
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+        Ok(WindowPresenter { window })
+    }
+}
+
+impl Presenter for WindowPresenter {
+    fn present(&mut self, frame: &FrameBuffer) -> Result<(), String> {
+        self.window
+            .update_with_buffer(frame.color.as_slice(), frame.width, frame.height)
+            .map_err(|e| e.to_string())
+    }
+
+    fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+
+    fn size(&self) -> (usize, usize) {
+        self.window.get_size()
+    }
+}
+
+impl InputSource for WindowPresenter {
+    fn poll(&mut self) -> InputState {
+        InputState {
+            keys_down: self.window.get_keys(),
+            keys_pressed: self.window.get_keys_pressed(KeyRepeat::No),
+            mouse_position: self.window.get_mouse_pos(MouseMode::Discard),
+            mouse_down: [MouseButton::Left, MouseButton::Middle, MouseButton::Right]
+                .into_iter()
+                .filter(|&button| self.window.get_mouse_down(button))
+                .collect(),
+            scroll: self.window.get_scroll_wheel().map_or(0.0, |(_, y)| y),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::ops::Range;
+
+use crate::drawer::DepthMode;
+use crate::frame_buffer::FrameBuffer;
+
+/// Rows per tile when `Drawer::render` splits the frame between threads.
+pub const TILE_ROWS: usize = 16;
+
+/// Full-width band of rows of a `FrameBuffer`. Tiles of one frame borrow
+/// disjoint slices of it, so each can be filled on its own thread.
+#[derive(Debug)]
+pub struct Tile<'a> {
+    /// First row of the frame covered by the tile.
+    pub top: i32,
+    pub width: usize,
+    pub color: &'a mut [u32],
+    pub depth: &'a mut [f32],
+}
+
+impl<'a> Tile<'a> {
+    /// The whole frame as one tile.
+    pub fn whole(target: &'a mut FrameBuffer) -> Tile<'a> {
+        Tile { top: 0, width: target.width, color: &mut target.color, depth: &mut target.depth }
+    }
+
+    /// Cuts the frame into tiles of `rows` rows, top to bottom. The last one
+    /// may be shorter.
+    pub fn split(target: &'a mut FrameBuffer, rows: usize) -> Vec<Tile<'a>> {
+        let width = target.width;
+        target
+            .color
+            .chunks_mut(width * rows)
+            .zip(target.depth.chunks_mut(width * rows))
+            .enumerate()
+            .map(|(i, (color, depth))| Tile { top: (i * rows) as i32, width, color, depth })
+            .collect()
+    }
+
+    pub fn rows(&self) -> Range<i32> {
+        self.top..self.top + (self.color.len() / self.width.max(1)) as i32
+    }
+
+    pub fn columns(&self) -> Range<i32> {
+        0..self.width as i32
+    }
+
+    /// Index into the tile's slices of a pixel inside it, if the pixel
+    /// passes the depth test.
+    pub fn depth_index(&self, depth_mode: DepthMode, x: i32, y: i32, z: f32) -> Option<usize> {
+        let index = (y - self.top) as usize * self.width + x as usize;
+        if depth_mode == DepthMode::PaintersSort || z < self.depth[index] {
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+/// Bits of sub-pixel precision: vertices snap to 1/256 of a pixel.
+pub const SUBPIXEL_BITS: u32 = 8;
+
+const ONE: i64 = 1 << SUBPIXEL_BITS;
+
+/// Fills a screen-space triangle by testing the center of every pixel in
+/// its bounding box against the three edges. Each point carries `N`
+/// attributes that are interpolated across the face; `plot` is called once
+/// per covered pixel within `rows` and `columns`, usually those of a `Tile`.
+///
+/// Positions are snapped to fixed point, so the edge tests are exact. A
+/// center that falls exactly on an edge belongs to the triangle only if the
+/// edge is a top or a left one, which draws pixels on an edge shared by two
+/// triangles exactly once. Every pixel gets the same attributes whichever
+/// tile it is walked for, so splitting a frame into tiles does not change
+/// the picture.
+pub fn fill_triangle<const N: usize>(
+    points: [(f32, f32, [f32; N]); 3],
+    rows: Range<i32>,
+    columns: Range<i32>,
+    mut plot: impl FnMut(i32, i32, &[f32; N])
+) {
+    let fixed = |v: f32| (v * ONE as f32).round() as i64;
+    let mut points = points.map(|(x, y, a)| ((fixed(x), fixed(y)), a));
+
+    // Twice the area; positive when the points run clockwise on screen.
+    let mut area = edge(points[0].0, points[1].0, points[2].0);
+    if area == 0 {
+        return;
+    }
+    if area < 0 {
+        points.swap(1, 2);
+        area = -area;
+    }
+
+    // Edge `i` runs between the other two points, so its value at a pixel,
+    // divided by the area, is the weight of point `i` there.
+    let edges = [(1, 2), (2, 0), (0, 1)].map(|(a, b)| (points[a].0, points[b].0));
+    let bias = edges.map(|(a, b)| {
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let top_left = dy < 0 || (dy == 0 && dx > 0);
+        if top_left { 0 } else { -1 }
+    });
+    let step_x = edges.map(|(a, b)| -(b.1 - a.1) * ONE);
+
+    let xs = points.map(|p| p.0 .0);
+    let ys = points.map(|p| p.0 .1);
+    let bounds = |v: [i64; 3]| {
+        let min = v[0].min(v[1]).min(v[2]) >> SUBPIXEL_BITS;
+        let max = v[0].max(v[1]).max(v[2]) >> SUBPIXEL_BITS;
+        (min as i32, max as i32)
+    };
+    let (min_x, max_x) = bounds(xs);
+    let (min_y, max_y) = bounds(ys);
+    let (x0, x1) = (min_x.max(columns.start), max_x.min(columns.end - 1));
+    let (y0, y1) = (min_y.max(rows.start), max_y.min(rows.end - 1));
+    if x0 > x1 {
+        return;
+    }
+
+    let center = |i: i32| ((i as i64) << SUBPIXEL_BITS) + ONE / 2;
+    let inv_area = 1.0_f32 / area as f32;
+    for y in y0..=y1 {
+        let mut w = edges.map(|(a, b)| edge(a, b, (center(x0), center(y))));
+        for x in x0..=x1 {
+            if (0..3).all(|i| w[i] + bias[i] >= 0) {
+                let weights = w.map(|w| w as f32 * inv_area);
+                let mut a = [0.0_f32; N];
+                for (k, value) in a.iter_mut().enumerate() {
+                    *value = weights[0] * points[0].1[k] + weights[1] * points[1].1[k] + weights[2] * points[2].1[k];
+                }
+                plot(x, y, &a);
+            }
+            for i in 0..3 {
+                w[i] += step_x[i];
+            }
+        }
+    }
+}
+
+/// Plots the pixels under a segment between two points in pixel
+/// coordinates, one per column or per row, whichever it spans more of.
+/// Each is the pixel the segment passes through at the center line of that
+/// column or row, with `z` interpolated there. The pixels do not depend on
+/// which end comes first.
+pub fn draw_line(from: (f32, f32, f32), to: (f32, f32, f32), mut plot: impl FnMut(i32, i32, f32)) {
+    let steep = (to.1 - from.1).abs() > (to.0 - from.0).abs();
+    // `u` runs along the longer axis, `v` across it.
+    let uvz = |(x, y, z): (f32, f32, f32)| if steep { (y, x, z) } else { (x, y, z) };
+    let (mut a, mut b) = (uvz(from), uvz(to));
+    if a.0 > b.0 {
+        std::mem::swap(&mut a, &mut b);
+    }
+    let length = b.0 - a.0;
+    if length <= 0.0 {
+        return;
+    }
+
+    for u in (a.0 - 0.5).ceil() as i32..(b.0 - 0.5).ceil() as i32 {
+        let t = (u as f32 + 0.5 - a.0) / length;
+        let v = (a.1 + (b.1 - a.1) * t).floor() as i32;
+        let z = a.2 + (b.2 - a.2) * t;
+        if steep {
+            plot(v, u, z);
+        } else {
+            plot(u, v, z);
+        }
+    }
+}
+
+/// Which side of the line from `a` to `b` the point `p` is on, scaled by
+/// the length of `a` to `b`. Positive to the right on screen, where y
+/// grows downwards.
+fn edge(a: (i64, i64), b: (i64, i64), p: (i64, i64)) -> i64 {
+    (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+}
@@ -0,0 +1,120 @@
+use crate::math::{
+    matrix4::Mat4,
+    mesh::{ bounds_of, Mesh, MeshLoadError },
+    quat::Quat,
+    vector4f::Vec4F,
+};
+
+/// Index into `Scene::meshes`. Several nodes may share one mesh.
+pub type MeshHandle = usize;
+
+/// Index into `Scene::nodes`.
+pub type NodeId = usize;
+
+/// Placement of a node relative to its parent: scaled first, then rotated,
+/// then translated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: Vec4F,
+    pub rotation: Quat,
+    pub scale: Vec4F,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            translation: Vec4F::new(0.0, 0.0, 0.0),
+            rotation: Quat::IDENTITY,
+            scale: Vec4F::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Transform {
+    pub fn from_translation(x: f32, y: f32, z: f32) -> Transform {
+        Transform { translation: Vec4F::new(x, y, z), ..Transform::default() }
+    }
+
+    pub fn matrix(&self) -> Mat4 {
+        let scale = Mat4::scale(self.scale.x, self.scale.y, self.scale.z);
+
+        scale * self.rotation.to_mat4() * Mat4::from_translation(self.translation)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Node {
+    pub name: String,
+    /// Nodes without a mesh only group and move their children.
+    pub mesh: Option<MeshHandle>,
+    pub transform: Transform,
+    /// The node's transform is relative to its parent's world transform.
+    pub parent: Option<NodeId>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scene {
+    pub meshes: Vec<Mesh>,
+    pub nodes: Vec<Node>,
+}
+
+impl Scene {
+    pub fn add_mesh(&mut self, mesh: Mesh) -> MeshHandle {
+        self.meshes.push(mesh);
+        self.meshes.len() - 1
+    }
+
+    /// Loads an OBJ file as a new mesh, see `Mesh::parse_obj_file`.
+    pub fn load_mesh(&mut self, path: &str) -> Result<MeshHandle, MeshLoadError> {
+        Ok(self.add_mesh(Mesh::parse_obj_file(path)?))
+    }
+
+    pub fn add_node(&mut self, node: Node) -> NodeId {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// Adds a root node showing `mesh`.
+    pub fn add_mesh_node(&mut self, name: &str, mesh: MeshHandle, transform: Transform) -> NodeId {
+        self.add_node(Node { name: name.to_string(), mesh: Some(mesh), transform, parent: None })
+    }
+
+    /// Transform from the node's model space to world space, through all of
+    /// its ancestors. A parent chain that loops is cut where it repeats.
+    pub fn world_matrix(&self, id: NodeId) -> Mat4 {
+        let mut matrix = self.nodes[id].transform.matrix();
+        let mut parent = self.nodes[id].parent;
+        let mut depth = 0;
+
+        while let Some(parent_id) = parent {
+            depth += 1;
+            if depth > self.nodes.len() {
+                break;
+            }
+            matrix = matrix * self.nodes[parent_id].transform.matrix();
+            parent = self.nodes[parent_id].parent;
+        }
+
+        matrix
+    }
+
+    /// World-space box around every node's mesh, `None` when nothing is
+    /// drawn.
+    pub fn bounds(&self) -> Option<(Vec4F, Vec4F)> {
+        let points = (0..self.nodes.len()).flat_map(|id| {
+            let matrix = self.world_matrix(id);
+            let tris = match self.nodes[id].mesh {
+                Some(mesh) => self.meshes[mesh].tris.as_slice(),
+                None => &[],
+            };
+            tris.iter().flat_map(move |tri| tri.p.map(|p| matrix * p))
+        });
+
+        bounds_of(points)
+    }
+
+    /// Warnings of every mesh, e.g. missing material libraries.
+    pub fn warnings(&self) -> impl Iterator<Item = &String> {
+        self.meshes.iter().flat_map(|mesh| mesh.warnings.iter())
+    }
+}
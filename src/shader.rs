@@ -0,0 +1,230 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::drawer::ColorStyle;
+use crate::light::{ shade, Light, Surface };
+use crate::math::{ color::Color, vector3f::Vec3F, vector4f::Vec4F };
+
+/// Most floats a vertex shader can pass to its fragment shader.
+pub const MAX_VARYINGS: usize = 16;
+
+/// Values a `VertexShader` computes per corner, interpolated across the
+/// triangle with perspective correction and handed to the `FragmentShader`.
+/// Read them back in the order they were added; `vec` and `color` take
+/// three slots each.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Varyings {
+    values: [f32; MAX_VARYINGS],
+    len: usize,
+}
+
+impl Varyings {
+    pub fn new() -> Varyings {
+        Varyings::default()
+    }
+
+    /// Panics past `MAX_VARYINGS`.
+    pub fn with(mut self, value: f32) -> Varyings {
+        assert!(self.len < MAX_VARYINGS, "more than {} varyings", MAX_VARYINGS);
+        self.values[self.len] = value;
+        self.len += 1;
+        self
+    }
+
+    pub fn with_vec(self, v: Vec4F) -> Varyings {
+        self.with(v.x).with(v.y).with(v.z)
+    }
+
+    pub fn with_color(self, c: Color) -> Varyings {
+        self.with(c.r).with(c.g).with(c.b)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> f32 {
+        self.values[index]
+    }
+
+    pub fn vec(&self, index: usize) -> Vec4F {
+        Vec4F::new(self.values[index], self.values[index + 1], self.values[index + 2])
+    }
+
+    pub fn color(&self, index: usize) -> Color {
+        Color::new(self.values[index], self.values[index + 1], self.values[index + 2])
+    }
+
+    pub fn lerp(&self, other: &Varyings, t: f32) -> Varyings {
+        let mut out = *self;
+        for i in 0..self.len {
+            out.values[i] += (other.values[i] - self.values[i]) * t;
+        }
+        out
+    }
+
+    /// Sum of the three corners' values scaled by `weights`.
+    pub fn blend(corners: &[Varyings; 3], weights: [f32; 3]) -> Varyings {
+        let mut out = corners[0];
+        for i in 0..out.len {
+            out.values[i] = corners[0].values[i] * weights[0]
+                + corners[1].values[i] * weights[1]
+                + corners[2].values[i] * weights[2];
+        }
+        out
+    }
+}
+
+/// Per-frame values every shader can read.
+#[derive(Clone, Copy, Debug)]
+pub struct Uniforms<'a> {
+    pub lights: &'a [Light],
+    pub ambient: Color,
+    /// Camera position in world space.
+    pub eye: Vec4F,
+    /// Applied by the built-in fragment shaders as their last step.
+    pub color_style: ColorStyle,
+}
+
+/// One corner of a visible triangle, in world space.
+#[derive(Clone, Copy, Debug)]
+pub struct VertexInput {
+    pub position: Vec4F,
+    /// Unit vertex normal.
+    pub normal: Vec4F,
+    pub uv: Vec3F,
+    /// Unit normal of the whole face.
+    pub face_normal: Vec4F,
+    pub face_center: Vec4F,
+    pub surface: Surface,
+}
+
+/// One covered pixel.
+#[derive(Clone, Copy, Debug)]
+pub struct FragmentInput {
+    pub x: i32,
+    pub y: i32,
+    /// From 0 at the near plane to 1 at the far one.
+    pub depth: f32,
+    pub uv: Vec3F,
+    /// The triangle's texture at `uv`, white when it has none.
+    pub texel: Color,
+    pub surface: Surface,
+}
+
+pub trait VertexShader: Send + Sync {
+    /// Runs for every corner of every triangle that survives culling.
+    fn vertex(&self, input: &VertexInput, uniforms: &Uniforms) -> Varyings;
+}
+
+pub trait FragmentShader: Send + Sync {
+    /// Color of a pixel that passed the depth test, or `None` to leave it
+    /// and its depth untouched. Runs on the tile threads.
+    fn fragment(&self, varyings: &Varyings, input: &FragmentInput, uniforms: &Uniforms) -> Option<Color>;
+}
+
+/// A vertex and a fragment shader that agree on their varyings.
+#[derive(Clone)]
+pub struct ShaderProgram {
+    pub vertex: Arc<dyn VertexShader>,
+    pub fragment: Arc<dyn FragmentShader>,
+}
+
+impl ShaderProgram {
+    pub fn new(vertex: impl VertexShader + 'static, fragment: impl FragmentShader + 'static) -> ShaderProgram {
+        ShaderProgram { vertex: Arc::new(vertex), fragment: Arc::new(fragment) }
+    }
+
+    /// A program from one type implementing both stages.
+    pub fn from_shader<S: VertexShader + FragmentShader + 'static>(shader: S) -> ShaderProgram {
+        let shader = Arc::new(shader);
+        ShaderProgram { vertex: shader.clone(), fragment: shader }
+    }
+}
+
+impl fmt::Debug for ShaderProgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ShaderProgram")
+    }
+}
+
+fn lit(input: &VertexInput, uniforms: &Uniforms, point: &Vec4F, normal: &Vec4F) -> Color {
+    shade(uniforms.lights, uniforms.ambient, &uniforms.eye, &input.surface, point, normal)
+}
+
+/// The texel times the color the vertex shader put first, in the frame's
+/// color style.
+fn textured_color(varyings: &Varyings, input: &FragmentInput, uniforms: &Uniforms) -> Option<Color> {
+    Some(uniforms.color_style.apply(input.texel * varyings.color(0)))
+}
+
+/// Lit once per face, at its center.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FlatShader;
+
+impl VertexShader for FlatShader {
+    fn vertex(&self, input: &VertexInput, uniforms: &Uniforms) -> Varyings {
+        Varyings::new().with_color(lit(input, uniforms, &input.face_center, &input.face_normal))
+    }
+}
+
+impl FragmentShader for FlatShader {
+    fn fragment(&self, varyings: &Varyings, input: &FragmentInput, uniforms: &Uniforms) -> Option<Color> {
+        textured_color(varyings, input, uniforms)
+    }
+}
+
+/// Lit at the corners, colors interpolated across the face.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GouraudShader;
+
+impl VertexShader for GouraudShader {
+    fn vertex(&self, input: &VertexInput, uniforms: &Uniforms) -> Varyings {
+        Varyings::new().with_color(lit(input, uniforms, &input.position, &input.normal))
+    }
+}
+
+impl FragmentShader for GouraudShader {
+    fn fragment(&self, varyings: &Varyings, input: &FragmentInput, uniforms: &Uniforms) -> Option<Color> {
+        textured_color(varyings, input, uniforms)
+    }
+}
+
+/// Position and normal interpolated across the face and lit per pixel.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PhongShader;
+
+impl VertexShader for PhongShader {
+    fn vertex(&self, input: &VertexInput, _: &Uniforms) -> Varyings {
+        Varyings::new().with_vec(input.position).with_vec(input.normal)
+    }
+}
+
+impl FragmentShader for PhongShader {
+    fn fragment(&self, varyings: &Varyings, input: &FragmentInput, uniforms: &Uniforms) -> Option<Color> {
+        let mut normal = varyings.vec(3);
+        let surface = Surface { diffuse: input.surface.diffuse * input.texel, ..input.surface };
+        let color = shade(uniforms.lights, uniforms.ambient, &uniforms.eye, &surface, &varyings.vec(0), &normal.normalize());
+        Some(uniforms.color_style.apply(color))
+    }
+}
+
+/// The surface's own color, no lighting.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UnlitShader;
+
+impl VertexShader for UnlitShader {
+    fn vertex(&self, input: &VertexInput, _: &Uniforms) -> Varyings {
+        Varyings::new().with_color(input.surface.diffuse)
+    }
+}
+
+impl FragmentShader for UnlitShader {
+    fn fragment(&self, varyings: &Varyings, input: &FragmentInput, uniforms: &Uniforms) -> Option<Color> {
+        textured_color(varyings, input, uniforms)
+    }
+}
@@ -0,0 +1,75 @@
+use std::io;
+use std::path::Path;
+
+use crate::image_import::load_image;
+use crate::math::color::Color;
+
+/// How texels are looked up between texel centers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureSampling {
+    Nearest,
+    Bilinear,
+}
+
+/// 0x00RRGGBB image sampled with wrapping (repeat) texture coordinates.
+/// `v = 0` is the bottom row, as in OBJ files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+}
+
+impl Texture {
+    pub fn new(width: usize, height: usize, pixels: Vec<u32>) -> Texture {
+        assert_eq!(pixels.len(), width * height, "texture size does not match pixel count");
+        Texture { width, height, pixels }
+    }
+
+    /// Loads a PNG, BMP or PPM file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Texture> {
+        let image = load_image(path)?;
+        if image.width == 0 || image.height == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "empty texture"));
+        }
+
+        Ok(Texture::new(image.width, image.height, image.pixels))
+    }
+
+    /// `size` x `size` checkerboard of `cells` x `cells` squares.
+    pub fn checkerboard(size: usize, cells: usize, a: u32, b: u32) -> Texture {
+        let cell = (size / cells.max(1)).max(1);
+        let pixels = (0..size * size)
+            .map(|i| if ((i % size) / cell + (i / size) / cell) & 1 == 0 { a } else { b })
+            .collect();
+
+        Texture::new(size, size, pixels)
+    }
+
+    fn texel(&self, x: i64, y: i64) -> u32 {
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.rem_euclid(self.height as i64) as usize;
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn sample(&self, u: f32, v: f32, sampling: TextureSampling) -> Color {
+        let x = u * self.width as f32;
+        let y = (1.0 - v) * self.height as f32;
+
+        match sampling {
+            TextureSampling::Nearest => Color::from_u32(self.texel(x.floor() as i64, y.floor() as i64)),
+            TextureSampling::Bilinear => {
+                // Shift by half a texel so weights are relative to texel centers.
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+
+                let texel = |x: i64, y: i64| Color::from_u32(self.texel(x, y));
+                let top = texel(x0, y0).lerp(&texel(x0 + 1, y0), fx);
+                let bottom = texel(x0, y0 + 1).lerp(&texel(x0 + 1, y0 + 1), fx);
+                top.lerp(&bottom, fy)
+            }
+        }
+    }
+}
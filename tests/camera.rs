@@ -0,0 +1,142 @@
+use std::f32::consts::PI;
+
+use minifb::MouseButton;
+use testy_rusty::camera::{Camera, MouseLook, OrbitCamera, Projection, MAX_PITCH};
+use testy_rusty::input::InputState;
+use testy_rusty::math::quat::Quat;
+use testy_rusty::math::vector4f::Vec4F;
+
+#[test]
+fn pitch_is_clamped_and_tilts_the_view() {
+    let mut camera = Camera::default();
+    camera.turn(0.0, 0.5);
+    camera.get_view_matrix();
+    assert!((camera.look_dir.y - 0.5_f32.sin()).abs() < 1e-5);
+
+    camera.turn(0.0, 10.0);
+    assert!((camera.yaw_pitch_roll().1 - MAX_PITCH).abs() < 1e-4);
+    camera.turn(0.3, -20.0);
+    let (yaw, pitch, roll) = camera.yaw_pitch_roll();
+    assert!((pitch + MAX_PITCH).abs() < 1e-4 && (yaw - 0.3).abs() < 1e-4 && roll.abs() < 1e-4);
+}
+
+#[test]
+fn yaw_pitch_and_roll_round_trip() {
+    let mut camera = Camera::default();
+    camera.set_yaw_pitch_roll(-1.2, 0.4, 0.3);
+
+    let (yaw, pitch, roll) = camera.yaw_pitch_roll();
+    assert!((yaw + 1.2).abs() < 1e-4, "{}", yaw);
+    assert!((pitch - 0.4).abs() < 1e-4, "{}", pitch);
+    assert!((roll - 0.3).abs() < 1e-4, "{}", roll);
+}
+
+#[test]
+fn slerped_orientation_passes_straight_up() {
+    let mut camera = Camera::default();
+    // Pitched up and over until looking nearly backwards, upside down.
+    let over = Quat::from_axis_angle(Vec4F::new(1.0, 0.0, 0.0), -0.9 * PI);
+
+    for t in [0.25, 0.5, 0.75] {
+        camera.orientation = Quat::IDENTITY.slerp(&over, t);
+        camera.get_view_matrix();
+
+        let angle = 0.9 * PI * t;
+        assert!(camera.look_dir.x.abs() < 1e-5, "t = {}", t);
+        assert!((camera.look_dir.y - angle.sin()).abs() < 1e-5, "t = {}", t);
+        assert!((camera.look_dir.z - angle.cos()).abs() < 1e-5, "t = {}", t);
+    }
+}
+
+#[test]
+fn mouse_look_turns_by_mouse_movement() {
+    let mut look = MouseLook::default();
+    look.enabled = true;
+    look.sensitivity = 0.01;
+
+    assert_eq!(look.update(Some((100.0, 100.0))), None);
+    let (yaw, pitch) = look.update(Some((110.0, 80.0))).unwrap();
+    assert!((yaw - 0.1).abs() < 1e-6 && (pitch - 0.2).abs() < 1e-6);
+
+    look.invert_y = true;
+    let (_, pitch) = look.update(Some((110.0, 90.0))).unwrap();
+    assert!((pitch - 0.1).abs() < 1e-6);
+
+    // Leaving the window or disabling never produces a jump.
+    assert_eq!(look.update(None), None);
+    assert_eq!(look.update(Some((0.0, 0.0))), None);
+    look.enabled = false;
+    assert_eq!(look.update(Some((50.0, 50.0))), None);
+}
+
+#[test]
+fn orbit_frames_and_looks_at_the_target() {
+    let mut camera = Camera { aspect_ratio: 1.0, ..Camera::default() };
+    let mut orbit = OrbitCamera::default();
+    orbit.yaw = 0.7;
+    orbit.pitch = -0.3;
+
+    orbit.frame(Vec4F::new(-1.0, -1.0, 3.0), Vec4F::new(1.0, 1.0, 5.0), &camera);
+    orbit.apply(&mut camera);
+
+    let radius = 3.0_f32.sqrt();
+    assert!((orbit.distance - radius / 37.5_f32.to_radians().sin()).abs() < 1e-4);
+    let mut offset = orbit.target - camera.position;
+    assert!((offset.length() - orbit.distance).abs() < 1e-4);
+    camera.get_view_matrix();
+    assert!((offset.normalize().dot_product(&camera.look_dir) - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn orbit_drags_and_zooms() {
+    let mut orbit = OrbitCamera::default();
+    let mut input = InputState { mouse_position: Some((10.0, 10.0)), ..InputState::default() };
+    orbit.update(&input);
+
+    input.mouse_position = Some((30.0, 0.0));
+    input.mouse_down = vec![MouseButton::Left];
+    orbit.update(&input);
+    assert!((orbit.yaw - 0.2).abs() < 1e-6 && (orbit.pitch - 0.1).abs() < 1e-6);
+
+    input.mouse_down = vec![MouseButton::Middle];
+    input.mouse_position = Some((40.0, 0.0));
+    orbit.update(&input);
+    assert!(orbit.target.length() > 0.0);
+    assert!((orbit.yaw - 0.2).abs() < 1e-6);
+
+    input.mouse_down.clear();
+    input.scroll = 1.0;
+    orbit.update(&input);
+    assert!((orbit.distance - 4.5).abs() < 1e-5);
+}
+
+fn project(camera: &Camera, point: Vec4F) -> (f32, f32) {
+    let p = camera.get_projection_matrix() * point;
+    (p.x / p.w, p.y / p.w)
+}
+
+#[test]
+fn lens_shift_moves_the_view_off_axis() {
+    let camera = Camera { aspect_ratio: 1.0, shift_x: 0.5, shift_y: -0.25, ..Camera::default() };
+
+    for depth in [1.0, 10.0] {
+        let (x, y) = project(&camera, Vec4F::new(0.0, 0.0, depth));
+        assert!((x + 0.5).abs() < 1e-5 && (y - 0.25).abs() < 1e-5, "{} {}", x, y);
+    }
+}
+
+#[test]
+fn orthographic_size_does_not_change_with_distance() {
+    let mut camera = Camera {
+        projection: Projection::Orthographic,
+        aspect_ratio: 0.5,
+        ortho_height: 4.0,
+        ..Camera::default()
+    };
+
+    assert_eq!(project(&camera, Vec4F::new(1.0, 1.0, 2.0)), (0.25, 0.5));
+    assert_eq!(project(&camera, Vec4F::new(1.0, 1.0, 200.0)), (0.25, 0.5));
+
+    camera.zoom = 2.0;
+    assert_eq!(project(&camera, Vec4F::new(1.0, 1.0, 2.0)), (0.5, 1.0));
+}
@@ -0,0 +1,76 @@
+use testy_rusty::camera::Projection;
+use testy_rusty::drawer::{clip_to_frustum, Drawer, ShadedTriangle, Triangle};
+use testy_rusty::math::vector4f::Vec4F;
+
+/// Pixels covered by the teapot, five units ahead, between `near` and `far`.
+fn covered(projection: Projection, near: f32, far: f32) -> usize {
+    let mut drawer = Drawer::new(160, 120);
+    drawer.ready("src/objects/teatpot.obj").unwrap();
+    drawer.camera.projection = projection;
+    drawer.camera.near = near;
+    drawer.camera.far = far;
+    drawer.render();
+
+    assert!(drawer.target.depth.iter().all(|&z| z == f32::INFINITY || (0.0..=1.0).contains(&z)));
+    drawer.target.color.iter().filter(|&&c| c != 0).count()
+}
+
+#[test]
+fn far_plane_cuts_off_distant_geometry() {
+    for projection in [Projection::Perspective, Projection::Orthographic] {
+        let whole = covered(projection, 0.05, 4000.0);
+        let cut = covered(projection, 0.05, 4.5);
+
+        assert!(whole > 0);
+        assert!(cut > 0 && cut < whole, "{:?}: {} of {}", projection, cut, whole);
+        assert_eq!(covered(projection, 0.05, 3.0), 0);
+    }
+}
+
+#[test]
+fn near_plane_follows_the_camera() {
+    for projection in [Projection::Perspective, Projection::Orthographic] {
+        let whole = covered(projection, 0.05, 4000.0);
+        let cut = covered(projection, 5.0, 4000.0);
+
+        assert!(cut > 0 && cut < whole, "{:?}: {} of {}", projection, cut, whole);
+        assert_eq!(covered(projection, 8.0, 4000.0), 0);
+    }
+}
+
+/// Twice the signed area of a clip-space triangle after the divide by `w`.
+fn screen_area(tri: &Triangle) -> f32 {
+    let [a, b, c] = tri.p.map(|p| (p.x / p.w, p.y / p.w));
+    (b.0 - a.0) * (c.1 - a.1) - (c.0 - a.0) * (b.1 - a.1)
+}
+
+#[test]
+fn clipped_triangles_keep_their_winding() {
+    // Corners at `w = 1`: one or two of them past the right plane (x > w)
+    // or behind the near plane (z < 0).
+    let cases = [
+        [(0.0, 0.0, 0.5), (3.0, 0.2, 0.5), (0.2, 0.8, 0.5)],
+        [(0.0, 0.0, 0.5), (3.0, 0.2, 0.5), (2.5, 0.8, 0.5)],
+        [(-0.5, 0.0, 0.5), (0.5, 0.1, -1.0), (0.0, 0.8, 0.5)],
+        [(-0.5, 0.0, -1.0), (0.5, 0.1, -1.0), (0.0, 0.8, 0.5)],
+    ];
+
+    for corners in cases {
+        for reversed in [false, true] {
+            for rotation in 0..3 {
+                let mut p = corners.map(|(x, y, z)| Vec4F { x, y, z, w: 1.0 });
+                if reversed {
+                    p.swap(1, 2);
+                }
+                p.rotate_left(rotation);
+                let tri = Triangle { p, ..Triangle::default() };
+
+                let clipped = clip_to_frustum(&ShadedTriangle { tri, ..ShadedTriangle::default() });
+                assert!(!clipped.is_empty());
+                for ShadedTriangle { tri: out, .. } in clipped {
+                    assert!(screen_area(&out) * screen_area(&tri) > 0.0, "{:?} from {:?}", out.p, tri.p);
+                }
+            }
+        }
+    }
+}
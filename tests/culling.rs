@@ -0,0 +1,103 @@
+use testy_rusty::drawer::{Color, CullMode, Drawer, Material, Mesh, ShadingMode, Triangle, Winding};
+use testy_rusty::light::Light;
+use testy_rusty::math::vector4f::Vec4F;
+use testy_rusty::scene::Transform;
+
+/// A right triangle five units ahead of the camera. Its corners run
+/// counter-clockwise on screen unless `reversed`.
+fn triangle(reversed: bool) -> Triangle {
+    let mut p = [Vec4F::new(0.0, 0.0, 0.0), Vec4F::new(0.0, 2.0, 0.0), Vec4F::new(2.0, 0.0, 0.0)];
+    if reversed {
+        p.swap(1, 2);
+    }
+    let toward_camera = Vec4F { w: 0.0, ..Vec4F::new(0.0, 0.0, -1.0) };
+
+    Triangle { p, n: [toward_camera; 3], color: Color::WHITE, ..Triangle::default() }
+}
+
+/// Colors of the frame after drawing `mesh`.
+fn render(mesh: Mesh) -> Vec<u32> {
+    render_with(mesh, |_| {})
+}
+
+fn render_with(mesh: Mesh, configure: impl FnOnce(&mut Drawer)) -> Vec<u32> {
+    let mut drawer = Drawer::new(40, 30);
+    configure(&mut drawer);
+    drawer.lights = vec![Light::directional(Vec4F::new(0.0, 0.0, 1.0), Color::WHITE)];
+    let mesh = drawer.scene.add_mesh(mesh);
+    drawer.scene.add_mesh_node("triangle", mesh, Transform::from_translation(0.0, 0.0, 5.0));
+    drawer.render();
+
+    drawer.target.color
+}
+
+fn drawn(pixels: &[u32]) -> usize {
+    pixels.iter().filter(|&&c| c != 0).count()
+}
+
+fn mesh(reversed: bool, cull_mode: CullMode, winding: Winding) -> Mesh {
+    Mesh { tris: vec![triangle(reversed)], cull_mode, winding, ..Mesh::default() }
+}
+
+#[test]
+fn cull_modes_pick_faces_by_winding() {
+    let ccw = Winding::CounterClockwise;
+    let front = drawn(&render(mesh(false, CullMode::Back, ccw)));
+    assert!(front > 0);
+
+    for (reversed, winding, cull_mode, visible) in [
+        (true, ccw, CullMode::Back, false),
+        (true, ccw, CullMode::Front, true),
+        (false, ccw, CullMode::Front, false),
+        (true, ccw, CullMode::None, true),
+        (false, ccw, CullMode::None, true),
+        (true, Winding::Clockwise, CullMode::Back, true),
+        (false, Winding::Clockwise, CullMode::Back, false),
+    ] {
+        let count = drawn(&render(mesh(reversed, cull_mode, winding)));
+        assert_eq!(count > 0, visible, "{:?} {:?} reversed: {}", cull_mode, winding, reversed);
+    }
+}
+
+#[test]
+fn back_faces_are_lit_like_front_faces() {
+    let front = render(mesh(false, CullMode::Back, Winding::CounterClockwise));
+    let back = render(mesh(true, CullMode::None, Winding::CounterClockwise));
+
+    // Lit by the light behind the camera, not just the ambient.
+    assert!(front.iter().any(|&c| c > 0x808080));
+    assert_eq!(front, back);
+}
+
+#[test]
+fn material_cull_mode_overrides_the_mesh() {
+    let mut back = mesh(true, CullMode::Back, Winding::CounterClockwise);
+    back.materials.push(Material { cull_mode: Some(CullMode::None), ..Material::default() });
+    assert_eq!(drawn(&render(back.clone())), 0);
+
+    back.tris[0].material = Some(0);
+    assert!(drawn(&render(back)) > 0);
+}
+
+#[test]
+fn drawer_cull_mode_overrides_materials_and_meshes() {
+    let mut back = mesh(true, CullMode::Back, Winding::CounterClockwise);
+    back.materials.push(Material { cull_mode: Some(CullMode::None), ..Material::default() });
+    back.tris[0].material = Some(0);
+
+    assert_eq!(drawn(&render_with(back.clone(), |drawer| drawer.cull_mode = Some(CullMode::Back))), 0);
+    assert!(drawn(&render_with(back, |drawer| drawer.cull_mode = Some(CullMode::Front))) > 0);
+}
+
+#[test]
+fn computed_normals_follow_the_winding() {
+    let obj = |face: &str| format!("v 0 0 0\nv 0 2 0\nv 2 0 0\nf {}\n", face);
+    let ccw = Mesh::parse_obj_str(&obj("1 2 3"), "ccw.obj").unwrap();
+    let cw = Mesh { winding: Winding::Clockwise, ..Mesh::parse_obj_str(&obj("1 3 2"), "cw.obj").unwrap() };
+
+    for mode in [ShadingMode::Flat, ShadingMode::Gouraud, ShadingMode::Phong] {
+        let front = render_with(ccw.clone(), |drawer| drawer.shading_mode = mode);
+        assert!(front.iter().any(|&c| c > 0x808080), "{:?}", mode);
+        assert_eq!(front, render_with(cw.clone(), |drawer| drawer.shading_mode = mode), "{:?}", mode);
+    }
+}
@@ -0,0 +1,351 @@
+//! Golden-image regression tests for the rasterizer.
+//!
+//! Every test renders one of the bundled models from a fixed camera pose and
+//! compares the frame with a reference image in `tests/golden`. On mismatch
+//! the rendered frame and a diff image are written to `target/golden-diff`.
+//!
+//! Run with `UPDATE_GOLDEN=1 cargo test --test golden` to (re)generate the
+//! references after an intentional change in the output.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use std::sync::Arc;
+
+use testy_rusty::camera::Projection;
+use testy_rusty::drawer::{CameraMode, Color, ColorStyle, CullMode, Drawer, RenderMode, ShadingMode};
+use testy_rusty::frame_buffer::FrameBuffer;
+use testy_rusty::image_export::{encode_ppm, save_image};
+use testy_rusty::image_import::load_image;
+use testy_rusty::light::Light;
+use testy_rusty::math::quat::Quat;
+use testy_rusty::math::vector4f::Vec4F;
+use testy_rusty::scene::{Node, Transform};
+use testy_rusty::texture::{Texture, TextureSampling};
+
+const WIDTH: usize = 160;
+const HEIGHT: usize = 120;
+
+/// Largest per-channel difference that still counts as the same pixel.
+const CHANNEL_TOLERANCE: u8 = 8;
+/// Fraction of pixels allowed to exceed `CHANNEL_TOLERANCE`.
+const MAX_BAD_PIXEL_RATIO: f32 = 0.002;
+
+struct Pose {
+    position: Vec4F,
+    yaw: f32,
+}
+
+/// `model` is a file in `src/objects`, or a path when it contains a slash.
+fn setup(model: &str, pose: &Pose) -> Drawer {
+    let path = if model.contains('/') { model.to_string() } else { format!("src/objects/{}", model) };
+    let mut drawer = Drawer::new(WIDTH, HEIGHT);
+    drawer.ready(&path).unwrap();
+    drawer.camera.aspect_ratio = HEIGHT as f32 / WIDTH as f32;
+    drawer.camera.position = pose.position;
+    drawer.camera.set_yaw_pitch_roll(pose.yaw, 0.0, 0.0);
+
+    drawer
+}
+
+fn render(model: &str, pose: &Pose) -> FrameBuffer {
+    render_with(model, pose, |_| {})
+}
+
+/// Renders after letting `configure` change drawer settings.
+fn render_with(model: &str, pose: &Pose, configure: impl FnOnce(&mut Drawer)) -> FrameBuffer {
+    let mut drawer = setup(model, pose);
+    configure(&mut drawer);
+    drawer.render();
+
+    drawer.target
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn diff_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("target").join("golden-diff")
+}
+
+fn load_ppm(path: &Path) -> Option<FrameBuffer> {
+    let image = load_image(path).ok()?;
+    let mut frame = FrameBuffer::new(image.width, image.height);
+    frame.color = image.pixels;
+
+    Some(frame)
+}
+
+fn channel_delta(a: u32, b: u32) -> u8 {
+    [16, 8, 0]
+        .iter()
+        .map(|shift| (((a >> shift) & 0xFF) as i32 - ((b >> shift) & 0xFF) as i32).unsigned_abs() as u8)
+        .max()
+        .unwrap_or(0)
+}
+
+fn check_golden(name: &str, model: &str, pose: Pose) {
+    compare_golden(name, render(model, &pose));
+}
+
+fn compare_golden(name: &str, actual: FrameBuffer) {
+    let reference_path = golden_dir().join(format!("{}.ppm", name));
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(golden_dir()).unwrap();
+        fs::write(&reference_path, encode_ppm(&actual)).unwrap();
+        return;
+    }
+
+    let expected = load_ppm(&reference_path).unwrap_or_else(|| {
+        panic!(
+            "missing or unreadable reference {}, run with UPDATE_GOLDEN=1 to create it",
+            reference_path.display()
+        )
+    });
+    assert_eq!(
+        (expected.width, expected.height),
+        (actual.width, actual.height),
+        "reference {} has a different resolution",
+        reference_path.display()
+    );
+
+    // Mismatching pixels are red in the diff, matching ones a dimmed copy of
+    // the reference so the failure is easy to locate.
+    let mut diff = FrameBuffer::new(actual.width, actual.height);
+    let mut bad_pixels = 0;
+    for (i, (&a, &e)) in actual.color.iter().zip(expected.color.iter()).enumerate() {
+        if channel_delta(a, e) > CHANNEL_TOLERANCE {
+            bad_pixels += 1;
+            diff.color[i] = 0xFF0000;
+        } else {
+            diff.color[i] = (e >> 2) & 0x3F3F3F;
+        }
+    }
+
+    let max_bad_pixels = (MAX_BAD_PIXEL_RATIO * actual.color.len() as f32) as usize;
+    if bad_pixels > max_bad_pixels {
+        fs::create_dir_all(diff_dir()).unwrap();
+        let actual_path = diff_dir().join(format!("{}.actual.png", name));
+        let diff_path = diff_dir().join(format!("{}.diff.png", name));
+        save_image(&actual, &actual_path).unwrap();
+        save_image(&diff, &diff_path).unwrap();
+
+        panic!(
+            "{}: {} pixels differ from the reference (allowed {}), see {} and {}",
+            name,
+            bad_pixels,
+            max_bad_pixels,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+#[test]
+fn ship_front() {
+    check_golden("ship_front", "ship.obj", Pose { position: Vec4F::new(0.0, 1.5, -1.0), yaw: 0.0 });
+}
+
+#[test]
+fn teapot_front() {
+    check_golden("teapot_front", "teatpot.obj", Pose { position: Vec4F::new(0.0, 1.5, -1.0), yaw: 0.0 });
+}
+
+#[test]
+fn teapot_turned() {
+    check_golden("teapot_turned", "teatpot.obj", Pose { position: Vec4F::new(-4.0, 2.0, 0.0), yaw: -0.7 });
+}
+
+#[test]
+fn donut_front() {
+    check_golden("donut_front", "donut.obj", Pose { position: Vec4F::new(0.0, 3.8, -4.0), yaw: 0.0 });
+}
+
+#[test]
+fn o_quads_front() {
+    check_golden("o_quads_front", "O.obj", Pose { position: Vec4F::new(0.0, 3.8, -4.0), yaw: 0.0 });
+}
+
+#[test]
+fn o_textured_nearest() {
+    let pose = Pose { position: Vec4F::new(0.0, 3.8, -4.0), yaw: 0.0 };
+    let frame = render_with("O.obj", &pose, |drawer| {
+        drawer.texture = Some(Arc::new(Texture::checkerboard(64, 8, 0xFF4020, 0x2040FF)));
+        drawer.texture_sampling = TextureSampling::Nearest;
+    });
+
+    compare_golden("o_textured_nearest", frame);
+}
+
+#[test]
+fn o_textured_bilinear() {
+    let pose = Pose { position: Vec4F::new(0.0, 3.8, -4.0), yaw: 0.0 };
+    let frame = render_with("O.obj", &pose, |drawer| {
+        drawer.texture = Some(Arc::new(Texture::checkerboard(8, 8, 0xFF4020, 0x2040FF)));
+        drawer.texture_sampling = TextureSampling::Bilinear;
+    });
+
+    compare_golden("o_textured_bilinear", frame);
+}
+
+#[test]
+fn teapot_tinted_light() {
+    let pose = Pose { position: Vec4F::new(0.0, 1.5, -1.0), yaw: 0.0 };
+    let frame = render_with("teatpot.obj", &pose, |drawer| {
+        drawer.lights[0].color = Color::new(1.0, 0.6, 0.2);
+    });
+
+    compare_golden("teapot_tinted_light", frame);
+}
+
+#[test]
+fn teapot_retro() {
+    let pose = Pose { position: Vec4F::new(0.0, 1.5, -1.0), yaw: 0.0 };
+    let frame = render_with("teatpot.obj", &pose, |drawer| {
+        drawer.color_style = ColorStyle::Retro;
+    });
+
+    compare_golden("teapot_retro", frame);
+}
+
+#[test]
+fn donut_gouraud() {
+    let pose = Pose { position: Vec4F::new(0.0, 3.8, -4.0), yaw: 0.0 };
+    let frame = render_with("donut.obj", &pose, |drawer| {
+        drawer.shading_mode = ShadingMode::Gouraud;
+    });
+
+    compare_golden("donut_gouraud", frame);
+}
+
+#[test]
+fn teapot_phong() {
+    let pose = Pose { position: Vec4F::new(0.0, 1.5, -1.0), yaw: 0.0 };
+    let frame = render_with("teatpot.obj", &pose, |drawer| {
+        drawer.shading_mode = ShadingMode::Phong;
+    });
+
+    compare_golden("teapot_phong", frame);
+}
+
+#[test]
+fn cube_point_and_spot_lights() {
+    let pose = Pose { position: Vec4F::new(2.0, 2.0, 1.0), yaw: 0.4 };
+    let frame = render_with("tests/data/cube.obj", &pose, |drawer| {
+        drawer.shading_mode = ShadingMode::Phong;
+        drawer.lights = vec![
+            Light::point(Vec4F::new(2.0, 3.0, 3.0), Color::new(1.0, 0.9, 0.7)),
+            Light::spot(Vec4F::new(-2.0, 4.0, 5.0), Vec4F::new(0.5, -1.0, 0.2), 0.2, 0.4, Color::new(0.3, 0.5, 1.0)),
+        ];
+    });
+
+    compare_golden("cube_point_and_spot_lights", frame);
+}
+
+#[test]
+fn scene_with_parented_nodes() {
+    let pose = Pose { position: Vec4F::new(1.5, 3.0, -2.0), yaw: 0.0 };
+    let frame = render_with("tests/data/cube.obj", &pose, |drawer| {
+        let scene = &mut drawer.scene;
+        scene.nodes[0].transform.rotation = Quat::from_euler(0.0, -0.5, 0.0);
+        let teapot = scene.load_mesh("src/objects/teatpot.obj").unwrap();
+        scene.add_node(Node {
+            name: "child".to_string(),
+            mesh: Some(0),
+            transform: Transform {
+                translation: Vec4F::new(3.0, 0.0, 0.0),
+                rotation: Quat::from_euler(0.4, 0.0, 0.0),
+                scale: Vec4F::new(0.5, 0.5, 0.5),
+            },
+            parent: Some(0),
+        });
+        scene.add_mesh_node("teapot", teapot, Transform::from_translation(-2.0, -1.0, 6.0));
+    });
+
+    compare_golden("scene_with_parented_nodes", frame);
+}
+
+#[test]
+fn mountains_pitched_and_rolled() {
+    let pose = Pose { position: Vec4F::new(0.0, 40.0, -110.0), yaw: 0.0 };
+    let frame = render_with("mountains.obj", &pose, |drawer| {
+        drawer.camera.turn(0.0, -0.3);
+        drawer.camera.roll(0.2);
+    });
+
+    compare_golden("mountains_pitched_and_rolled", frame);
+}
+
+#[test]
+fn mountains_from_below_unculled() {
+    let pose = Pose { position: Vec4F::new(0.0, -20.0, -110.0), yaw: 0.0 };
+    let frame = render_with("mountains.obj", &pose, |drawer| {
+        drawer.camera.turn(0.0, 0.25);
+        drawer.scene.meshes[0].cull_mode = CullMode::None;
+    });
+
+    compare_golden("mountains_from_below_unculled", frame);
+}
+
+#[test]
+fn teapot_orbit_framed() {
+    let pose = Pose { position: Vec4F::new(0.0, 0.0, 0.0), yaw: 0.6 };
+    let frame = render_with("teatpot.obj", &pose, |drawer| {
+        drawer.camera.turn(0.0, -0.4);
+        drawer.set_camera_mode(CameraMode::Orbit);
+    });
+
+    compare_golden("teapot_orbit_framed", frame);
+}
+
+#[test]
+fn cube_orthographic_off_axis() {
+    let pose = Pose { position: Vec4F::new(0.0, 0.0, 0.0), yaw: 0.5 };
+    let frame = render_with("tests/data/cube.obj", &pose, |drawer| {
+        drawer.camera.turn(0.0, -0.5);
+        drawer.set_camera_mode(CameraMode::Orbit);
+        drawer.set_projection(Projection::Orthographic);
+        drawer.camera.shift_x = 0.3;
+    });
+
+    compare_golden("cube_orthographic_off_axis", frame);
+}
+
+#[test]
+fn bublik_hidden_line() {
+    let pose = Pose { position: Vec4F::new(0.0, 2.0, -2.0), yaw: 0.0 };
+    let frame = render_with("bublik.obj", &pose, |drawer| {
+        drawer.render_mode = RenderMode::HiddenLine;
+    });
+
+    compare_golden("bublik_hidden_line", frame);
+}
+
+#[test]
+fn cube_solid_with_edges() {
+    let pose = Pose { position: Vec4F::new(2.0, 2.0, 1.0), yaw: 0.4 };
+    let frame = render_with("tests/data/cube.obj", &pose, |drawer| {
+        drawer.render_mode = RenderMode::SolidWithEdges;
+        drawer.line_color = Color::new(1.0, 0.8, 0.0);
+    });
+
+    compare_golden("cube_solid_with_edges", frame);
+}
+
+#[test]
+fn cube_materials() {
+    check_golden("cube_materials", "tests/data/cube.obj", Pose { position: Vec4F::new(2.0, 2.0, 1.0), yaw: 0.4 });
+}
+
+#[test]
+fn bublik_above() {
+    check_golden("bublik_above", "bublik.obj", Pose { position: Vec4F::new(0.0, 2.0, -2.0), yaw: 0.0 });
+}
+
+#[test]
+fn mountains_far() {
+    check_golden("mountains_far", "mountains.obj", Pose { position: Vec4F::new(0.0, 25.0, -110.0), yaw: 0.0 });
+}
@@ -0,0 +1,72 @@
+use testy_rusty::frame_buffer::FrameBuffer;
+use testy_rusty::image_export::{encode, ImageFormat};
+use testy_rusty::image_import::{decode, load_image};
+
+fn test_frame() -> FrameBuffer {
+    let mut frame = FrameBuffer::new(7, 5);
+    for (i, col) in frame.color.iter_mut().enumerate() {
+        *col = (i as u32 * 0x0A1B2C) & 0xFFFFFF;
+    }
+
+    frame
+}
+
+#[test]
+fn round_trips_every_format() {
+    let frame = test_frame();
+    for format in [ImageFormat::Ppm, ImageFormat::Bmp, ImageFormat::Png] {
+        let image = decode(&encode(&frame, format), format).unwrap();
+
+        assert_eq!((image.width, image.height), (frame.width, frame.height), "{:?}", format);
+        assert_eq!(image.pixels, frame.color, "{:?}", format);
+    }
+}
+
+#[test]
+fn decodes_compressed_and_filtered_png() {
+    // Written by zlib at level 9 with every scanline filter type in turn.
+    let image = load_image("tests/data/gradient.png").unwrap();
+
+    assert_eq!((image.width, image.height), (16, 12));
+    for y in 0..12u32 {
+        for x in 0..16u32 {
+            let expected = (((x * 16) & 255) << 16) | (((y * 20) & 255) << 8) | ((x * y * 3) & 255);
+            assert_eq!(image.pixels[(y * 16 + x) as usize], expected, "pixel {}, {}", x, y);
+        }
+    }
+}
+
+#[test]
+fn rejects_truncated_data() {
+    let bytes = encode(&test_frame(), ImageFormat::Png);
+
+    assert!(decode(&bytes[..bytes.len() / 2], ImageFormat::Png).is_err());
+    assert!(decode(b"P6\n7 5\n255\n", ImageFormat::Ppm).is_err());
+    assert!(decode(b"BM", ImageFormat::Bmp).is_err());
+}
+
+#[test]
+fn rejects_oversized_headers() {
+    let mut bytes = encode(&test_frame(), ImageFormat::Png);
+    // Width and height of the IHDR chunk; chunk CRCs are not checked.
+    bytes[16..24].fill(0xFF);
+
+    assert!(decode(&bytes, ImageFormat::Png).is_err());
+    assert!(decode(b"P6\n18446744073709551615 2\n255\n", ImageFormat::Ppm).is_err());
+
+    let mut bytes = encode(&test_frame(), ImageFormat::Bmp);
+    // 0x7FFFFFFF by 0x7FFFFFFF at 32 bits per pixel.
+    bytes[18..26].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0x7F, 0xFF, 0xFF, 0xFF, 0x7F]);
+    bytes[28..30].copy_from_slice(&32u16.to_le_bytes());
+    assert!(decode(&bytes, ImageFormat::Bmp).is_err());
+}
+
+#[test]
+fn rejects_palette_of_partial_entries() {
+    let mut bytes = encode(&test_frame(), ImageFormat::Png);
+    // A four byte PLTE chunk right after IHDR.
+    let plte = [&[0, 0, 0, 4][..], b"PLTE", &[1, 2, 3, 4], &[0; 4]].concat();
+    bytes.splice(33..33, plte);
+
+    assert!(decode(&bytes, ImageFormat::Png).is_err());
+}
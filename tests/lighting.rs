@@ -0,0 +1,66 @@
+use testy_rusty::light::{shade, Attenuation, Light, LightKind, Surface};
+use testy_rusty::math::color::Color;
+use testy_rusty::math::vector4f::Vec4F;
+
+const MATTE: Surface = Surface { diffuse: Color::WHITE, specular: Color::BLACK, shininess: 0.0 };
+
+fn close(a: Color, b: Color) -> bool {
+    (a.r - b.r).abs() < 1e-4 && (a.g - b.g).abs() < 1e-4 && (a.b - b.b).abs() < 1e-4
+}
+
+#[test]
+fn directional_light_follows_lambert() {
+    let lights = [Light::directional(Vec4F::new(0.0, -1.0, 0.0), Color::WHITE)];
+    let eye = Vec4F::new(0.0, 5.0, 0.0);
+    let point = Vec4F::new(0.0, 0.0, 0.0);
+
+    let facing = shade(&lights, Color::gray(0.1), &eye, &MATTE, &point, &Vec4F::new(0.0, 1.0, 0.0));
+    let tilted = shade(&lights, Color::gray(0.1), &eye, &MATTE, &point, &Vec4F::new(0.6, 0.8, 0.0));
+    let away = shade(&lights, Color::gray(0.1), &eye, &MATTE, &point, &Vec4F::new(0.0, -1.0, 0.0));
+
+    assert!(close(facing, Color::gray(1.1)));
+    assert!(close(tilted, Color::gray(0.9)));
+    assert!(close(away, Color::gray(0.1)));
+}
+
+#[test]
+fn point_light_attenuates_with_distance() {
+    let attenuation = Attenuation { constant: 1.0, linear: 0.5, quadratic: 0.25 };
+    let light = Light {
+        kind: LightKind::Point { position: Vec4F::new(0.0, 2.0, 0.0), attenuation },
+        color: Color::WHITE,
+    };
+
+    let (to_light, incoming) = light.incoming(&Vec4F::new(0.0, 0.0, 0.0)).unwrap();
+
+    assert_eq!(to_light, Vec4F::new(0.0, 1.0, 0.0));
+    assert!(close(incoming, Color::gray(1.0 / 3.0)));
+}
+
+#[test]
+fn spot_light_fades_between_cone_angles() {
+    let mut light = Light::spot(Vec4F::new(0.0, 1.0, 0.0), Vec4F::new(0.0, -1.0, 0.0), 0.2, 0.6, Color::WHITE);
+    if let LightKind::Spot { attenuation, .. } = &mut light.kind {
+        *attenuation = Attenuation::NONE;
+    }
+    let at_angle = |angle: f32| light.incoming(&Vec4F::new(angle.tan(), 0.0, 0.0)).map(|(_, c)| c.r);
+
+    assert_eq!(at_angle(0.1), Some(1.0));
+    let halfway = at_angle(0.4).unwrap();
+    assert!(halfway > 0.0 && halfway < 1.0);
+    assert_eq!(at_angle(0.7), None);
+}
+
+#[test]
+fn specular_highlight_peaks_at_mirror_direction() {
+    let lights = [Light::directional(Vec4F::new(-1.0, -1.0, 0.0), Color::WHITE)];
+    let shiny = Surface { diffuse: Color::BLACK, specular: Color::WHITE, shininess: 32.0 };
+    let point = Vec4F::new(0.0, 0.0, 0.0);
+    let normal = Vec4F::new(0.0, 1.0, 0.0);
+
+    let mirror = shade(&lights, Color::BLACK, &Vec4F::new(-1.0, 1.0, 0.0), &shiny, &point, &normal);
+    let off = shade(&lights, Color::BLACK, &Vec4F::new(1.0, 1.0, 0.0), &shiny, &point, &normal);
+
+    assert!(close(mirror, Color::WHITE));
+    assert!(off.r < 0.01);
+}
@@ -0,0 +1,86 @@
+use testy_rusty::math::matrix4::Mat4;
+use testy_rusty::math::vector4f::Vec4F;
+
+fn close(a: &Mat4, b: &Mat4) -> bool {
+    (0..4).all(|i| (0..4).all(|j| (a.m[i][j] - b.m[i][j]).abs() < 1e-5))
+}
+
+fn sample() -> Mat4 {
+    Mat4::scale(2.0, 0.5, 3.0)
+        * Mat4::from_axis_angle(Vec4F::new(1.0, 2.0, -0.5), 0.7)
+        * Mat4::translate(4.0, -1.0, 2.5)
+}
+
+#[test]
+fn inverse_undoes_the_matrix() {
+    let m = sample();
+    let inverse = m.inverse().unwrap();
+
+    assert!(close(&(m * inverse), &Mat4::make_identity()));
+    assert!(close(&(inverse * m), &Mat4::make_identity()));
+}
+
+#[test]
+fn singular_matrix_has_no_inverse() {
+    assert_eq!(Mat4::scale(1.0, 0.0, 1.0).inverse(), None);
+    assert_eq!(Mat4::default().inverse(), None);
+}
+
+#[test]
+fn determinant_multiplies_scale_factors() {
+    assert!((Mat4::scale(2.0, 0.5, 3.0).determinant() - 3.0).abs() < 1e-5);
+    assert!((Mat4::rotate_y(1.2).determinant() - 1.0).abs() < 1e-5);
+    assert!((sample().determinant() - 3.0).abs() < 1e-4);
+}
+
+#[test]
+fn add_and_sub_use_both_operands() {
+    let a = Mat4::make_identity();
+    let b = Mat4::scale(2.0, 3.0, 4.0);
+
+    assert_eq!((a + b).m[1][1], 4.0);
+    assert_eq!((b - a).m[2][2], 3.0);
+    assert!(close(&(b - b), &Mat4::default()));
+}
+
+#[test]
+fn axis_angle_matches_axis_rotations() {
+    assert!(close(&Mat4::from_axis_angle(Vec4F::new(1.0, 0.0, 0.0), 0.4), &Mat4::rotate_x(0.4)));
+    // `rotate_y` has the opposite handedness.
+    assert!(close(&Mat4::from_axis_angle(Vec4F::new(0.0, 3.0, 0.0), -1.1), &Mat4::rotate_y(1.1)));
+    assert!(close(&Mat4::from_axis_angle(Vec4F::new(0.0, 0.0, 1.0), 2.0), &Mat4::rotate_z(2.0)));
+}
+
+#[test]
+fn normal_matrix_keeps_normals_perpendicular() {
+    // A 45 degree slope squashed along x: the transformed normal must stay
+    // perpendicular to the transformed surface direction.
+    let m = Mat4::scale(0.25, 1.0, 1.0);
+    let along = m * Vec4F { w: 0.0, ..Vec4F::new(1.0, 1.0, 0.0) };
+    let normal = m.normal_matrix() * Vec4F { w: 0.0, ..Vec4F::new(1.0, -1.0, 0.0) };
+
+    assert!(along.dot_product(&normal).abs() < 1e-5);
+}
+
+#[test]
+fn centered_frustum_matches_project() {
+    let (near, far) = (0.05, 4000.0);
+    let half_height = near * 37.5_f32.to_radians().tan();
+    let half_width = half_height / 0.5625;
+    let frustum = Mat4::frustum(-half_width, half_width, -half_height, half_height, near, far);
+
+    assert!(close(&frustum, &Mat4::project(75.0, 0.5625, near, far)));
+}
+
+#[test]
+fn orthographic_maps_the_box_to_the_unit_cube() {
+    let m = Mat4::orthographic(-4.0, 2.0, -1.0, 3.0, 0.5, 10.5);
+
+    let low = m * Vec4F::new(-4.0, -1.0, 0.5);
+    let high = m * Vec4F::new(2.0, 3.0, 10.5);
+
+    for (p, expected) in [(low, [-1.0, -1.0, 0.0, 1.0]), (high, [1.0, 1.0, 1.0, 1.0])] {
+        let got = [p.x, p.y, p.z, p.w];
+        assert!(got.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-5), "{}", p);
+    }
+}
@@ -0,0 +1,185 @@
+use testy_rusty::math::color::Color;
+use testy_rusty::math::material::Material;
+use testy_rusty::math::mesh::{Mesh, MeshLoadErrorKind};
+use testy_rusty::math::vector4f::Vec4F;
+
+const SQUARE: &str = "\
+# no exporter header
+o Square
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 -1
+s 1
+f 1/1/1 2/2/1 3/3/1 4/4/1
+";
+
+#[test]
+fn triangulates_quads() {
+    let mesh = Mesh::parse_obj_str(SQUARE, "square.obj").unwrap();
+
+    assert_eq!(mesh.tris.len(), 2);
+    assert_eq!(mesh.tris[0].p, [Vec4F::new(0.0, 0.0, 0.0), Vec4F::new(1.0, 0.0, 0.0), Vec4F::new(1.0, 1.0, 0.0)]);
+    assert_eq!(mesh.tris[1].p, [Vec4F::new(0.0, 0.0, 0.0), Vec4F::new(1.0, 1.0, 0.0), Vec4F::new(0.0, 1.0, 0.0)]);
+}
+
+#[test]
+fn loads_normals_and_computes_missing_ones() {
+    let square = Mesh::parse_obj_str(SQUARE, "square.obj").unwrap();
+    assert!(square.tris.iter().all(|tri| tri.n == [Vec4F::new(0.0, 0.0, -1.0); 3]));
+
+    // Two faces folded along the x axis: the big one lies in the xy plane,
+    // the small one in the xz plane. Shared corners lean towards the big one.
+    let source = "\
+v 0 0 0
+v 1 0 0
+v 0 4 0
+v 0 0 -1
+vn 0 0 2
+f 1 2 3
+f 2 1 4//1
+";
+    let mesh = Mesh::parse_obj_str(source, "fold.obj").unwrap();
+
+    let shared = mesh.tris[0].n[0];
+    assert!((shared.length() - 1.0).abs() < 1e-5);
+    assert!((shared.z - 4.0 / 17.0_f32.sqrt()).abs() < 1e-5);
+    assert!((shared.y + 1.0 / 17.0_f32.sqrt()).abs() < 1e-5);
+    assert_eq!(mesh.tris[0].n[0], mesh.tris[0].n[1]);
+    assert_eq!(mesh.tris[0].n[2], Vec4F::new(0.0, 0.0, 1.0));
+    assert_eq!(mesh.tris[1].n[2], Vec4F::new(0.0, 0.0, 1.0));
+}
+
+#[test]
+fn accepts_all_index_forms_and_negative_indices() {
+    let source = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vt 0 0
+vn 0 0 1
+f 1 2 3
+f 1/1 2/1 3/1
+f 1//1 2//1 3//1
+f -3/-1/-1 -2/-1/-1 -1/-1/-1
+";
+    let mesh = Mesh::parse_obj_str(source, "forms.obj").unwrap();
+
+    assert_eq!(mesh.tris.len(), 4);
+    assert!(mesh.tris.iter().all(|tri| tri.p == mesh.tris[0].p));
+}
+
+#[test]
+fn joins_continued_lines_and_records_groups() {
+    let source = "\
+o First
+v 0 0 0
+v 1 0 \\
+  0
+v 0 1 0
+f 1 2 3
+g Second part
+f 3 2 1
+";
+    let mesh = Mesh::parse_obj_str(source, "groups.obj").unwrap();
+
+    assert_eq!(mesh.tris[0].p[1], Vec4F::new(1.0, 0.0, 0.0));
+    assert_eq!(mesh.groups.len(), 2);
+    assert_eq!((mesh.groups[0].name.as_str(), mesh.groups[0].start, mesh.groups[0].end), ("First", 0, 1));
+    assert_eq!((mesh.groups[1].name.as_str(), mesh.groups[1].start, mesh.groups[1].end), ("Second part", 1, 2));
+}
+
+#[test]
+fn reports_position_of_bad_index() {
+    let err = Mesh::parse_obj_str("v 0 0 0\nv 1 0 0\nf 1 2  5\n", "bad.obj").unwrap_err();
+
+    assert_eq!((err.line, err.column), (3, 8));
+    assert!(matches!(err.kind, MeshLoadErrorKind::IndexOutOfRange { index: 5, count: 2 }));
+    assert_eq!(err.to_string(), "bad.obj:3:8: index 5 out of range, 2 elements defined");
+}
+
+#[test]
+fn reports_invalid_numbers() {
+    let err = Mesh::parse_obj_str("v 0 zero 0\n", "bad.obj").unwrap_err();
+
+    assert_eq!((err.line, err.column), (1, 5));
+    assert!(matches!(err.kind, MeshLoadErrorKind::InvalidNumber(ref token) if token == "zero"));
+}
+
+#[test]
+fn loads_every_bundled_model() {
+    for model in ["O.obj", "bublik.obj", "conetri.obj", "donut.obj", "mountains.obj", "ship.obj", "teatpot.obj"] {
+        let mesh = Mesh::parse_obj_file(&format!("src/objects/{}", model)).unwrap();
+        assert!(!mesh.tris.is_empty(), "{} has no triangles", model);
+    }
+}
+
+#[test]
+fn assigns_materials_per_triangle() {
+    let mesh = Mesh::parse_obj_file("tests/data/cube.obj").unwrap();
+
+    assert!(mesh.warnings.is_empty(), "{:?}", mesh.warnings);
+    let names: Vec<&str> = mesh.materials.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["Red", "Green", "Blue", "Checker"]);
+
+    let used: Vec<Option<usize>> = mesh.tris.iter().map(|tri| tri.material).collect();
+    assert_eq!(used, [Some(0), Some(0), Some(0), Some(0), Some(1), Some(1), Some(1), Some(1), Some(3), Some(3), Some(2), Some(2)]);
+    assert!(mesh.materials[3].diffuse_map.is_some());
+}
+
+#[test]
+fn parses_mtl_statements() {
+    let source = "\
+Kd 1 0 0
+newmtl Shiny Metal
+Ka 0.5
+Kd 1.0 0.5 0.0
+Ks 0.2 0.2 0.2
+Ns 96.5
+Tr 0.25
+map_Bump -bm 0.5 no_such_bump.png
+";
+    let mut warnings = Vec::new();
+    let materials = Material::parse_mtl_str(source, "tests/data/inline.mtl", &mut warnings).unwrap();
+
+    assert_eq!(materials.len(), 1);
+    let material = &materials[0];
+    assert_eq!(material.name, "Shiny Metal");
+    assert_eq!(
+        (material.ambient, material.diffuse, material.specular),
+        (Color::gray(0.5), Color::new(1.0, 0.5, 0.0), Color::gray(0.2))
+    );
+    assert_eq!((material.shininess, material.dissolve), (96.5, 0.75));
+    assert!(material.bump_map.is_none());
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].starts_with("tests/data/inline.mtl:8:1: cannot load"), "{}", warnings[0]);
+}
+
+#[test]
+fn skips_spectral_and_xyz_colors_with_a_warning() {
+    let source = "newmtl Odd\nKd spectral curve.rfl 1.0\nKs xyz 0.5 0.5 0.5\nKa 0.25\n";
+    let mut warnings = Vec::new();
+    let materials = Material::parse_mtl_str(source, "tests/data/odd.mtl", &mut warnings).unwrap();
+
+    let material = &materials[0];
+    let default = Material::default();
+    assert_eq!((material.diffuse, material.specular), (default.diffuse, default.specular));
+    assert_eq!(material.ambient, Color::gray(0.25));
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0].starts_with("tests/data/odd.mtl:2:4: unsupported spectral"), "{}", warnings[0]);
+}
+
+#[test]
+fn keeps_geometry_when_material_library_is_missing() {
+    let source = "mtllib nowhere.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl Gone\nf 1 2 3\n";
+    let mesh = Mesh::parse_obj_str(source, "tests/data/missing.obj").unwrap();
+
+    assert_eq!(mesh.tris.len(), 1);
+    assert_eq!(mesh.tris[0].material, None);
+    assert_eq!(mesh.warnings.len(), 2);
+}
@@ -0,0 +1,77 @@
+use std::f32::consts::FRAC_PI_2;
+
+use testy_rusty::camera::Camera;
+use testy_rusty::math::matrix4::Mat4;
+use testy_rusty::math::quat::Quat;
+use testy_rusty::math::vector4f::Vec4F;
+
+fn close_mat(a: &Mat4, b: &Mat4) -> bool {
+    (0..4).all(|i| (0..4).all(|j| (a.m[i][j] - b.m[i][j]).abs() < 1e-5))
+}
+
+fn close_vec(a: Vec4F, b: Vec4F) -> bool {
+    (a.x - b.x).abs() < 1e-5 && (a.y - b.y).abs() < 1e-5 && (a.z - b.z).abs() < 1e-5
+}
+
+#[test]
+fn matches_matrix_rotations() {
+    let axis = Vec4F::new(0.3, -1.0, 2.0);
+    let q = Quat::from_axis_angle(axis, 0.9);
+
+    assert!(close_mat(&q.to_mat4(), &Mat4::from_axis_angle(axis, 0.9)));
+
+    let v = Vec4F::new(1.0, 2.0, 3.0);
+    assert!(close_vec(q.rotate(v), q.to_mat4() * v));
+}
+
+#[test]
+fn product_applies_right_operand_first() {
+    let a = Quat::from_axis_angle(Vec4F::new(1.0, 0.0, 0.0), 0.5);
+    let b = Quat::from_axis_angle(Vec4F::new(0.0, 0.0, 1.0), 1.2);
+
+    // Row-vector matrices compose the other way round.
+    assert!(close_mat(&(a * b).to_mat4(), &(b.to_mat4() * a.to_mat4())));
+}
+
+#[test]
+fn euler_and_axis_angle_round_trip() {
+    let (x, y, z) = Quat::from_euler(0.3, -0.7, 2.1).to_euler();
+    assert!((x - 0.3).abs() < 1e-5 && (y + 0.7).abs() < 1e-5 && (z - 2.1).abs() < 1e-5);
+
+    let (axis, angle) = Quat::from_axis_angle(Vec4F::new(0.0, 2.0, 0.0), 1.0).to_axis_angle();
+    assert!(close_vec(axis, Vec4F::new(0.0, 1.0, 0.0)));
+    assert!((angle - 1.0).abs() < 1e-5);
+
+    let from_euler = Quat::from_euler(0.0, 0.0, FRAC_PI_2);
+    assert!(close_vec(from_euler.rotate(Vec4F::new(1.0, 0.0, 0.0)), Vec4F::new(0.0, 1.0, 0.0)));
+}
+
+#[test]
+fn slerp_moves_at_constant_speed() {
+    let start = Quat::IDENTITY;
+    let end = Quat::from_axis_angle(Vec4F::new(0.0, 1.0, 0.0), 2.0);
+
+    for t in [0.0, 0.25, 0.5, 1.0] {
+        let (_, angle) = start.slerp(&end, t).to_axis_angle();
+        assert!((angle - 2.0 * t).abs() < 1e-4, "t = {}: {}", t, angle);
+    }
+
+    // The long way round is never taken.
+    let negated = Quat::new(-end.x, -end.y, -end.z, -end.w);
+    let (_, angle) = start.slerp(&negated, 0.5).to_axis_angle();
+    assert!((angle - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn camera_orientation_round_trips() {
+    let mut camera = Camera::default();
+    camera.set_yaw_pitch_roll(0.8, 0.0, 0.0);
+    let orientation = camera.orientation;
+
+    camera.set_yaw_pitch_roll(-2.0, 0.0, 0.0);
+    camera.orientation = orientation;
+
+    let (yaw, pitch, _) = camera.yaw_pitch_roll();
+    assert!((yaw - 0.8).abs() < 1e-5);
+    assert!(pitch.abs() < 1e-5);
+}
@@ -0,0 +1,96 @@
+use testy_rusty::raster::{draw_line, fill_triangle};
+
+const SIZE: i32 = 32;
+
+/// How many times each pixel of a `SIZE` square gets plotted.
+fn coverage(triangles: &[[(f32, f32); 3]]) -> Vec<u32> {
+    let mut counts = vec![0; (SIZE * SIZE) as usize];
+    for tri in triangles {
+        fill_triangle(tri.map(|(x, y)| (x, y, [])), 0..SIZE, 0..SIZE, |x, y, _: &[f32; 0]| {
+            counts[(y * SIZE + x) as usize] += 1;
+        });
+    }
+
+    counts
+}
+
+#[test]
+fn shared_edges_are_drawn_exactly_once() {
+    // A fan around an off-grid center, with both windings, covering the
+    // square from (2, 2) to (30, 30) with sub-pixel corners.
+    let center = (15.3, 16.7);
+    let ring = [(2.0, 2.0), (17.25, 2.0), (30.0, 2.0), (30.0, 19.6), (30.0, 30.0), (9.1, 30.0), (2.0, 30.0), (2.0, 11.5)];
+    let triangles: Vec<[(f32, f32); 3]> = (0..ring.len())
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+            if i % 2 == 0 { [center, a, b] } else { [b, a, center] }
+        })
+        .collect();
+
+    let counts = coverage(&triangles);
+
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let inside = (2..30).contains(&x) && (2..30).contains(&y);
+            assert_eq!(counts[(y * SIZE + x) as usize], inside as u32, "pixel {}, {}", x, y);
+        }
+    }
+}
+
+#[test]
+fn centers_on_an_edge_go_to_the_top_left_one() {
+    // Centers of row 4 and column 4 lie exactly on the edges.
+    let counts = coverage(&[[(4.5, 4.5), (8.5, 4.5), (4.5, 8.5)]]);
+    let plotted = |x: i32, y: i32| counts[(y * SIZE + x) as usize] == 1;
+
+    assert!(plotted(4, 4) && plotted(7, 4) && plotted(4, 7));
+    // The diagonal is a bottom-right edge: centers on it are left out.
+    assert!(!plotted(8, 4) && !plotted(5, 7) && !plotted(4, 8));
+    assert_eq!(counts.iter().sum::<u32>(), 10);
+}
+
+#[test]
+fn thin_and_degenerate_triangles() {
+    assert_eq!(coverage(&[[(1.0, 1.0), (9.0, 9.0), (5.0, 5.0)]]).iter().sum::<u32>(), 0);
+
+    // A sliver narrower than a pixel still covers the centers it crosses.
+    let counts = coverage(&[[(10.4, 0.0), (10.6, 0.0), (10.6, 20.0)]]);
+    assert!(counts.iter().sum::<u32>() > 0);
+    assert!(counts.iter().enumerate().all(|(i, &c)| c == 0 || i as i32 % SIZE == 10));
+}
+
+#[test]
+fn attributes_are_interpolated_at_pixel_centers() {
+    let points = [(0.0, 0.0, [0.0, 0.0]), (16.0, 0.0, [16.0, 0.0]), (0.0, 16.0, [0.0, 16.0])];
+
+    fill_triangle(points, 0..SIZE, 0..SIZE, |x, y, &[u, v]| {
+        assert!((u - (x as f32 + 0.5)).abs() < 1e-4, "{} at {}", u, x);
+        assert!((v - (y as f32 + 0.5)).abs() < 1e-4, "{} at {}", v, y);
+    });
+}
+
+#[test]
+fn lines_plot_one_pixel_per_step_either_way_round() {
+    for (from, to) in [((1.2, 3.7, 0.0), (25.9, 9.1, 1.0)), ((4.5, 2.0, 0.0), (7.0, 29.5, 1.0))] {
+        let mut forward = Vec::new();
+        draw_line(from, to, |x, y, z| forward.push((x, y, z)));
+        let mut backward = Vec::new();
+        draw_line(to, from, |x, y, z| backward.push((x, y, z)));
+
+        assert_eq!(forward.len(), backward.len());
+        for (&(x0, y0, z0), &(x1, y1, z1)) in forward.iter().zip(&backward) {
+            assert_eq!((x0, y0), (x1, y1));
+            assert!((z0 - z1).abs() < 1e-6);
+        }
+        // Consecutive pixels touch, and depth grows along the line.
+        for pair in forward.windows(2) {
+            let ((x0, y0, z0), (x1, y1, z1)) = (pair[0], pair[1]);
+            assert!((x1 - x0).abs() <= 1 && (y1 - y0).abs() <= 1 && (x0, y0) != (x1, y1));
+            assert!(z1 > z0);
+        }
+    }
+
+    let mut count = 0;
+    draw_line((3.0, 3.0, 0.0), (3.0, 3.0, 0.0), |_, _, _| count += 1);
+    assert_eq!(count, 0);
+}
@@ -0,0 +1,65 @@
+use testy_rusty::drawer::{Color, Drawer, RenderMode};
+use testy_rusty::frame_buffer::FrameBuffer;
+use testy_rusty::math::vector4f::Vec4F;
+
+const RED: u32 = 0xFF0000;
+
+fn render(mode: RenderMode) -> FrameBuffer {
+    let mut drawer = Drawer::new(160, 120);
+    drawer.ready("src/objects/bublik.obj").unwrap();
+    // From above, looking into the hole, whose far side faces the camera
+    // but is hidden behind the near side.
+    drawer.camera.position = Vec4F::new(0.0, 2.0, -2.0);
+    drawer.render_mode = mode;
+    drawer.line_color = Color::new(1.0, 0.0, 0.0);
+    drawer.render();
+
+    drawer.target
+}
+
+fn drawn(frame: &FrameBuffer) -> Vec<bool> {
+    frame.color.iter().map(|&c| c != 0).collect()
+}
+
+#[test]
+fn hidden_line_leaves_out_edges_behind_faces() {
+    let wireframe = render(RenderMode::Wireframe);
+    let hidden = render(RenderMode::HiddenLine);
+
+    assert!(wireframe.color.iter().chain(&hidden.color).all(|&c| c == 0 || c == RED));
+    assert!(wireframe.depth.iter().all(|&z| z == f32::INFINITY));
+
+    let (wireframe, hidden) = (drawn(&wireframe), drawn(&hidden));
+    assert!(hidden.iter().zip(&wireframe).all(|(&h, &w)| !h || w));
+    let (h, w) = (hidden.iter().filter(|&&d| d).count(), wireframe.iter().filter(|&&d| d).count());
+    assert!(h > 0 && h < w * 95 / 100, "{} of {}", h, w);
+}
+
+#[test]
+fn solid_with_edges_draws_over_the_solid_frame() {
+    let solid = render(RenderMode::Solid);
+    let edges = render(RenderMode::SolidWithEdges);
+
+    let mut lines = 0;
+    for (&s, &e) in solid.color.iter().zip(&edges.color) {
+        if e == RED {
+            lines += 1;
+        } else {
+            assert_eq!(s, e);
+        }
+    }
+    let covered = solid.color.iter().filter(|&&c| c != 0).count();
+    assert!(lines > covered / 10 && lines < covered, "{} of {}", lines, covered);
+}
+
+#[test]
+fn points_are_depth_tested_corners() {
+    let frame = render(RenderMode::Points);
+
+    let points = frame.color.iter().filter(|&&c| c == RED).count();
+    assert!(points > 100);
+    for (&c, &z) in frame.color.iter().zip(&frame.depth) {
+        assert_eq!(c == RED, (0.0..=1.0).contains(&z));
+        assert!(c == 0 || c == RED);
+    }
+}
@@ -0,0 +1,43 @@
+use testy_rusty::drawer::Drawer;
+use testy_rusty::frame_buffer::FrameBuffer;
+
+#[test]
+fn resize_reallocates_and_keeps_the_aspect_ratio() {
+    let mut drawer = Drawer::new(160, 120);
+    drawer.ready("src/objects/teatpot.obj").unwrap();
+
+    drawer.resize(90, 160);
+    assert_eq!((drawer.target.width, drawer.target.height), (90, 160));
+    assert_eq!(drawer.target.color.len(), 90 * 160);
+    assert_eq!(drawer.camera.aspect_ratio, 160.0 / 90.0);
+
+    // Rendering after a resize must stay inside the new buffers.
+    drawer.render();
+    assert!(drawer.target.color.iter().any(|&c| c != 0));
+}
+
+#[test]
+fn render_scale_shrinks_the_target_only() {
+    let mut drawer = Drawer::new(160, 120);
+
+    drawer.set_render_scale(0.5);
+    assert_eq!((drawer.target.width, drawer.target.height), (80, 60));
+    assert_eq!(drawer.output_size(), (160, 120));
+
+    drawer.resize(200, 100);
+    assert_eq!((drawer.target.width, drawer.target.height), (100, 50));
+    assert_eq!(drawer.camera.aspect_ratio, 0.5);
+
+    drawer.set_render_scale(0.01);
+    assert_eq!(drawer.render_scale(), 0.125);
+}
+
+#[test]
+fn scaled_to_repeats_pixels() {
+    let mut frame = FrameBuffer::new(2, 1);
+    frame.color = vec![1, 2];
+
+    let scaled = frame.scaled_to(4, 2);
+
+    assert_eq!(scaled.color, vec![1, 1, 2, 2, 1, 1, 2, 2]);
+}
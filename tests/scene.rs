@@ -0,0 +1,57 @@
+use testy_rusty::math::mesh::Mesh;
+use testy_rusty::math::quat::Quat;
+use testy_rusty::math::vector4f::Vec4F;
+use testy_rusty::scene::{Node, Scene, Transform};
+
+fn close(a: Vec4F, b: Vec4F) -> bool {
+    (a.x - b.x).abs() < 1e-5 && (a.y - b.y).abs() < 1e-5 && (a.z - b.z).abs() < 1e-5
+}
+
+#[test]
+fn transform_scales_then_rotates_then_translates() {
+    let transform = Transform {
+        translation: Vec4F::new(10.0, 0.0, 0.0),
+        rotation: Quat::from_axis_angle(Vec4F::new(0.0, 0.0, 1.0), std::f32::consts::FRAC_PI_2),
+        scale: Vec4F::new(2.0, 1.0, 1.0),
+    };
+
+    let moved = transform.matrix() * Vec4F::new(1.0, 0.0, 0.0);
+
+    assert!(close(moved, Vec4F::new(10.0, 2.0, 0.0)), "{}", moved);
+}
+
+#[test]
+fn children_follow_their_parents() {
+    let mut scene = Scene::default();
+    let mesh = scene.add_mesh(Mesh::default());
+    let root = scene.add_mesh_node("root", mesh, Transform::from_translation(0.0, 0.0, 5.0));
+    scene.nodes[root].transform.scale = Vec4F::new(2.0, 2.0, 2.0);
+    let child = scene.add_node(Node {
+        name: "child".to_string(),
+        mesh: Some(mesh),
+        transform: Transform::from_translation(1.0, 0.0, 0.0),
+        parent: Some(root),
+    });
+    let grandchild = scene.add_node(Node {
+        name: "grandchild".to_string(),
+        mesh: None,
+        transform: Transform::from_translation(0.0, 1.0, 0.0),
+        parent: Some(child),
+    });
+
+    let origin = Vec4F::new(0.0, 0.0, 0.0);
+    assert!(close(scene.world_matrix(child) * origin, Vec4F::new(2.0, 0.0, 5.0)));
+    assert!(close(scene.world_matrix(grandchild) * origin, Vec4F::new(2.0, 2.0, 5.0)));
+
+    scene.nodes[root].transform.translation = Vec4F::new(0.0, 0.0, 0.0);
+    assert!(close(scene.world_matrix(grandchild) * origin, Vec4F::new(2.0, 2.0, 0.0)));
+}
+
+#[test]
+fn parent_cycles_do_not_hang() {
+    let mut scene = Scene::default();
+    let a = scene.add_node(Node { parent: Some(1), ..Node::default() });
+    scene.add_node(Node { parent: Some(a), ..Node::default() });
+
+    let _ = scene.world_matrix(a);
+}
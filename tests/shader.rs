@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use testy_rusty::drawer::{ColorStyle, Drawer, ShadingMode};
+use testy_rusty::frame_buffer::FrameBuffer;
+use testy_rusty::math::color::Color;
+use testy_rusty::math::vector4f::Vec4F;
+use testy_rusty::shader::{
+    FragmentInput, FragmentShader, PhongShader, ShaderProgram, Uniforms, VertexInput, VertexShader, Varyings,
+};
+use testy_rusty::texture::Texture;
+
+fn render(setup: impl FnOnce(&mut Drawer)) -> FrameBuffer {
+    let mut drawer = Drawer::new(160, 120);
+    drawer.ready("src/objects/teatpot.obj").unwrap();
+    setup(&mut drawer);
+    drawer.render();
+
+    drawer.target
+}
+
+/// Colors every pixel with the world height of the surface under it.
+struct Height;
+
+impl VertexShader for Height {
+    fn vertex(&self, input: &VertexInput, _: &Uniforms) -> Varyings {
+        Varyings::new().with(input.position.y)
+    }
+}
+
+impl FragmentShader for Height {
+    fn fragment(&self, varyings: &Varyings, _: &FragmentInput, _: &Uniforms) -> Option<Color> {
+        Some(Color::gray(varyings.get(0) / 3.0))
+    }
+}
+
+/// Plain red on odd columns, nothing on even ones.
+struct Stripes;
+
+impl FragmentShader for Stripes {
+    fn fragment(&self, _: &Varyings, input: &FragmentInput, _: &Uniforms) -> Option<Color> {
+        (input.x % 2 == 1).then(|| Color::new(1.0, 0.0, 0.0))
+    }
+}
+
+#[test]
+fn varyings_are_read_back_in_order() {
+    let varyings = Varyings::new().with(2.0).with_vec(Vec4F::new(3.0, 4.0, 5.0)).with_color(Color::new(0.1, 0.2, 0.3));
+
+    assert_eq!(varyings.len(), 7);
+    assert_eq!(varyings.get(0), 2.0);
+    assert_eq!(varyings.vec(1).y, 4.0);
+    assert_eq!(varyings.color(4).b, 0.3);
+    assert!(Varyings::new().is_empty());
+}
+
+#[test]
+fn varyings_blend_by_weight() {
+    let corners = [Varyings::new().with(0.0), Varyings::new().with(10.0), Varyings::new().with(20.0)];
+
+    assert_eq!(Varyings::blend(&corners, [0.5, 0.25, 0.25]).get(0), 7.5);
+    assert_eq!(corners[1].lerp(&corners[2], 0.25).get(0), 12.5);
+}
+
+#[test]
+fn built_in_program_matches_shading_mode() {
+    let mode = render(|drawer| drawer.shading_mode = ShadingMode::Phong);
+    let program = render(|drawer| drawer.shader = Some(ShaderProgram::from_shader(PhongShader)));
+
+    assert!(mode.color == program.color);
+}
+
+#[test]
+fn custom_program_replaces_the_lighting() {
+    let frame = render(|drawer| drawer.shader = Some(ShaderProgram::from_shader(Height)));
+    let lit = render(|_| {});
+
+    let covered: Vec<usize> = (0..frame.color.len()).filter(|&i| frame.depth[i] != f32::INFINITY).collect();
+    assert_eq!(covered.len(), lit.color.iter().filter(|&&c| c != 0).count());
+    // Gray everywhere, and lighter towards the top of the teapot.
+    assert!(covered.iter().all(|&i| frame.color[i] & 0xFF == (frame.color[i] >> 16) & 0xFF));
+    let brightness = |row: usize| (0..160).map(|x| frame.color[row * 160 + x] & 0xFF).max().unwrap();
+    let rows: Vec<usize> = covered.iter().map(|&i| i / 160).collect();
+    let (top, bottom) = (*rows.iter().min().unwrap(), *rows.iter().max().unwrap());
+    assert!(brightness(top + 2) > brightness(bottom - 2));
+}
+
+#[test]
+fn discarded_fragments_leave_the_target_untouched() {
+    let frame = render(|drawer| drawer.shader = Some(ShaderProgram::new(Height, Stripes)));
+    let full = render(|drawer| drawer.shader = Some(ShaderProgram::from_shader(Height)));
+
+    for (i, &color) in frame.color.iter().enumerate() {
+        if i % 160 % 2 == 0 {
+            assert_eq!((color, frame.depth[i]), (0, f32::INFINITY), "pixel {}", i);
+        } else {
+            assert_eq!(color != 0, full.depth[i] != f32::INFINITY, "pixel {}", i);
+            assert_eq!(frame.depth[i].to_bits(), full.depth[i].to_bits());
+        }
+    }
+}
+
+#[test]
+fn retro_style_is_applied_after_texturing() {
+    let levels: Vec<u32> = (0..13).map(|step| (step as f32 / 12.0 * 255.0).round() as u32).collect();
+
+    for mode in [ShadingMode::Flat, ShadingMode::Gouraud, ShadingMode::Phong, ShadingMode::Unlit] {
+        let mut drawer = Drawer::new(160, 120);
+        drawer.ready("src/objects/O.obj").unwrap();
+        drawer.texture = Some(Arc::new(Texture::checkerboard(64, 8, 0xFF4020, 0x2040FF)));
+        drawer.color_style = ColorStyle::Retro;
+        drawer.shading_mode = mode;
+        drawer.render();
+
+        let color = &drawer.target.color;
+        assert!(color.iter().any(|&c| c != 0), "{:?}", mode);
+        for &c in color {
+            let channels = [c >> 16, (c >> 8) & 0xFF, c & 0xFF];
+            assert!(channels.iter().all(|ch| levels.contains(ch)), "{:?} drew {:06X}", mode, c);
+        }
+    }
+}
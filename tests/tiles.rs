@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use testy_rusty::drawer::{DepthMode, Drawer, RenderMode, ShadingMode};
+use testy_rusty::frame_buffer::FrameBuffer;
+use testy_rusty::math::vector4f::Vec4F;
+use testy_rusty::texture::Texture;
+
+fn render(model: &str, setup: impl Fn(&mut Drawer), parallel: bool) -> FrameBuffer {
+    let mut drawer = Drawer::new(173, 131);
+    drawer.ready(model).unwrap();
+    drawer.camera.position = Vec4F::new(0.3, 0.5, 1.0);
+    drawer.parallel = parallel;
+    setup(&mut drawer);
+
+    // More threads than this machine may have, so tiles really do finish
+    // out of order.
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+    pool.install(|| drawer.render());
+
+    drawer.target
+}
+
+fn assert_same_in_parallel(model: &str, setup: impl Fn(&mut Drawer)) {
+    let single = render(model, &setup, false);
+    let tiled = render(model, &setup, true);
+
+    assert!(single.color.iter().any(|&c| c != 0));
+    assert!(single.color == tiled.color, "colors differ for {}", model);
+    assert!(single.depth.iter().zip(&tiled.depth).all(|(a, b)| a.to_bits() == b.to_bits()));
+}
+
+#[test]
+fn tiles_match_single_thread() {
+    assert_same_in_parallel("src/objects/teatpot.obj", |_| {});
+    assert_same_in_parallel("src/objects/teatpot.obj", |drawer| drawer.shading_mode = ShadingMode::Phong);
+    assert_same_in_parallel("src/objects/bublik.obj", |drawer| {
+        drawer.shading_mode = ShadingMode::Gouraud;
+        drawer.depth_mode = DepthMode::PaintersSort;
+    });
+    assert_same_in_parallel("src/objects/bublik.obj", |drawer| drawer.render_mode = RenderMode::HiddenLine);
+}
+
+#[test]
+fn textured_tiles_match_single_thread() {
+    let texture = Arc::new(Texture::checkerboard(64, 8, 0xFFFFFF, 0xFF3311));
+
+    assert_same_in_parallel("tests/data/cube.obj", |_| {});
+    assert_same_in_parallel("src/objects/O.obj", |drawer| drawer.texture = Some(texture.clone()));
+}